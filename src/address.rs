@@ -2,11 +2,23 @@ use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 use crate::logger::Attempt;
+use crate::passphrase::decode_hex_plain;
+use crate::SEPARATOR;
 use anyhow::{bail, format_err, Result};
-use bitcoin::bip32::{ChildNumber, Xpub};
-use bitcoin::{Address, Network};
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv, Xpub};
+use bitcoin::hashes::hmac::{Hmac, HmacEngine};
+use bitcoin::hashes::{sha512, Hash, HashEngine};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{
+    Address, Network, PrivateKey, PubkeyHash, PublicKey, ScriptBuf, ScriptHash, WPubkeyHash,
+};
 
 const MAX_DERIVATIONS: usize = 100;
+// BIP32 stores the depth of a key in a single byte
+const MAX_DERIVATION_DEPTH: usize = 255;
+// Indices at or above 2^31 are only reachable as hardened nodes written with 'h' or '
+const MAX_DERIVATION_INDEX: u32 = (1 << 31) - 1;
+const BIP39_PBKDF2_ROUNDS: usize = 2048;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct AddressValid {
@@ -22,6 +34,13 @@ pub struct Derivations {
 }
 
 impl Derivations {
+    fn empty() -> Self {
+        Self {
+            derivations: vec![],
+            args: vec![],
+        }
+    }
+
     /// Args that are exploded in the hashes file
     pub fn args(&self) -> Vec<String> {
         self.args.clone()
@@ -31,6 +50,26 @@ impl Derivations {
     pub fn hash_ratio(&self) -> f64 {
         self.derivations.len() as f64 / self.args.len() as f64
     }
+
+    /// Each hash line arg with the number of derivations hashcat explodes it into
+    pub fn batches(&self) -> Vec<(String, u64)> {
+        let mut batches = vec![];
+        for arg in &self.args {
+            let mut total = 0;
+            for path in arg.split(",") {
+                let mut nodes = 1_u64;
+                for node in path.split("/") {
+                    if let Some(num) = node.strip_prefix("?") {
+                        let num = num.trim_end_matches(['h', '\'']);
+                        nodes *= num.parse::<u64>().unwrap_or(0) + 1;
+                    }
+                }
+                total += nodes;
+            }
+            batches.push((arg.clone(), total));
+        }
+        batches
+    }
 }
 
 impl Attempt for Derivations {
@@ -47,17 +86,24 @@ impl Attempt for Derivations {
     }
 }
 
-const ERR_MSG: &str = "\nDerivation path should be valid comma or path-separated:
+const ERR_MSG: &str = "\nDerivation paths should be separated by ',' '|' or spaces:
   Address #0 from an unhardened path: 'm/0/0'
   Address #2 from a hardened path:    'm/44h/0h/0h/0/2'
   You can try multiple paths:         'm/0/0,m/44h/0h/0h/0/0'
   '?' attempts all paths from 0-11:   'm/0/?11'
+  '{}' attempts specific paths:       'm/0/{0,5,9}'
 
   Master XPUB does not require a derivation path and is ~2x faster to guess
   Try to use the exact derivation path for the address you have (see https://walletsrecovery.org/)\n";
 
 impl AddressValid {
     pub fn from_arg(address: &str, derivation: &Option<String>) -> Result<Self> {
+        let address = Self::normalize(address)?;
+        if let Ok(xpub) = Xpub::from_str(&address) {
+            if !is_master(xpub) && derivation.is_some() {
+                return Self::from_account_xpub(xpub, derivation.as_ref().expect("is some"));
+            }
+        }
         let kind = Self::kind(&address)?;
 
         if kind.is_xpub && derivation.is_some() {
@@ -66,7 +112,143 @@ impl AddressValid {
 
         let derivations = Self::derivation(&kind, derivation, MAX_DERIVATIONS)?;
 
-        Ok(Self::new(address.to_string(), kind, derivations))
+        Ok(Self::new(address, kind, derivations))
+    }
+
+    /// Targets the first receive address of an account-level XPUB exported from `path`
+    fn from_account_xpub(xpub: Xpub, path: &str) -> Result<Self> {
+        let origin = DerivationPath::from_str(path.trim())
+            .map_err(|_| format_err!("Account XPUB path '{}' is invalid{}", path, ERR_MSG))?;
+        let last = origin.into_iter().last().copied();
+        if origin.len() != xpub.depth as usize || last != Some(xpub.child_number) {
+            bail!(
+                "Account XPUB is at depth {} which does not match the path '{}'",
+                xpub.depth,
+                path
+            );
+        }
+        let kind = match origin.into_iter().next() {
+            Some(ChildNumber::Hardened { index }) => bip_kind(*index),
+            _ => None,
+        };
+        let Some(kind) = kind else {
+            bail!(
+                "Account XPUB path '{}' must start with m/44', m/49' or m/84'",
+                path
+            );
+        };
+        let kind = address_kinds().into_iter().find(|k| k.key == kind);
+        let kind = kind.expect("kind exists");
+
+        let secp = Secp256k1::verification_only();
+        let receive = DerivationPath::from_str("m/0/0").expect("Valid path");
+        let key = xpub.derive_pub(&secp, &receive)?.to_pub();
+        let mut valid = Self::new(String::new(), kind.clone(), Derivations::empty());
+        valid.formatted = valid.address_of(&key)?;
+        let derivation = Some(format!("{}/0/0", path.trim()));
+        valid.derivations = Self::derivation(&kind, &derivation, MAX_DERIVATIONS)?;
+        Ok(valid)
+    }
+
+    /// The standard path of a BIP44, BIP49 or BIP84 wallet e.g. BIP84 uses m/84'/0'/account'/0/index
+    pub fn bip_derivation(bip: u32, account: u32, index: u32) -> Result<String> {
+        if bip == 86 {
+            bail!("BIP86 (Taproot) addresses are not supported yet");
+        }
+        if bip_kind(bip).is_none() {
+            bail!(
+                "BIP '{}' is unknown, use 44 (Legacy), 49 (Nested Segwit) or 84 (Native Segwit)",
+                bip
+            );
+        }
+        Ok(format!("m/{}'/0'/{}'/0/{}", bip, account, index))
+    }
+
+    /// Whether the address type is the one derived by a BIP e.g. P2WPKH for BIP84
+    pub fn is_bip(&self, bip: u32) -> bool {
+        bip_kind(bip) == Some(self.kind.key.as_str())
+    }
+
+    /// Also checks the change address (chain 1) of every receive path (chain 0)
+    pub fn with_change(&self) -> Result<Self> {
+        if self.kind.is_xpub {
+            bail!("XPUBs are matched before the receive or change chain is derived so --include-change is not needed");
+        }
+        let mut change = vec![];
+        for derivation in &self.derivations.derivations {
+            let mut nodes: Vec<_> = derivation.split('/').collect();
+            if nodes.len() > 2 && nodes[nodes.len() - 2] == "0" {
+                let chain = nodes.len() - 2;
+                nodes[chain] = "1";
+                change.push(nodes.join("/"));
+            }
+        }
+        if change.is_empty() {
+            bail!(
+                "No derivation paths have a receive chain like 'm/0/0' to also check the change of"
+            );
+        }
+        let paths = [self.derivations.derivations.clone(), change].concat();
+        let derivations = Self::derivation(&self.kind, &Some(paths.join(",")), MAX_DERIVATIONS)?;
+        Ok(Self::new(
+            self.formatted.clone(),
+            self.kind.clone(),
+            derivations,
+        ))
+    }
+
+    /// Checks every address index in a range like '0-100' at the end of each derivation path
+    pub fn with_address_range(&self, range: &str) -> Result<Self> {
+        if self.kind.is_xpub {
+            bail!("XPUBs are matched before any address index is derived so --address-range is not needed");
+        }
+        let err = || format_err!("Address range '{}' should look like '0-100'", range);
+        let (start, end) = range.trim().split_once('-').ok_or_else(err)?;
+        let start = start.parse::<u32>().map_err(|_| err())?;
+        let end = end.parse::<u32>().map_err(|_| err())?;
+        if start > end {
+            return Err(err());
+        }
+
+        let mut parents = vec![];
+        for derivation in &self.derivations.derivations {
+            let parent = derivation
+                .rsplit_once('/')
+                .map_or("m", |(parent, _)| parent);
+            if !parents.contains(&parent) {
+                parents.push(parent);
+            }
+        }
+        let mut paths = vec![];
+        for parent in parents {
+            match start {
+                0 => paths.push(format!("{}/?{}", parent, end)),
+                _ => paths.extend((start..=end).map(|i| format!("{}/{}", parent, i))),
+            }
+        }
+        let derivation = Some(paths.join(","));
+        let derivations = Self::derivation(&self.kind, &derivation, MAX_DERIVATIONS)?;
+        Ok(Self::new(
+            self.formatted.clone(),
+            self.kind.clone(),
+            derivations,
+        ))
+    }
+
+    /// Removes whitespace from copy-pasting and lowercases bech32 addresses that are all uppercase
+    fn normalize(address: &str) -> Result<String> {
+        let address = address.trim();
+        let lowercase = address.to_lowercase();
+        if !lowercase.starts_with("bc1") || address == lowercase {
+            return Ok(address.to_string());
+        }
+        if address != address.to_uppercase() {
+            bail!(
+                "Bech32 address '{}' cannot mix uppercase and lowercase letters",
+                address
+            );
+        }
+        Ok(lowercase)
     }
 
     pub fn new(formatted: String, kind: AddressKind, derivations: Derivations) -> Self {
@@ -77,6 +259,135 @@ impl AddressValid {
         }
     }
 
+    /// Derives the first addresses of each kind from an xpub to check it matches a wallet
+    pub fn xpub_addresses(&self, count: u32) -> Result<Vec<String>> {
+        if !self.kind.is_xpub {
+            bail!("Only an XPUB can be used to derive addresses");
+        }
+        let xpub = Xpub::from_str(&self.formatted).map_err(|_| format_err!("Invalid XPUB"))?;
+        let secp = Secp256k1::verification_only();
+        let mut addresses = vec![];
+        for i in 0..count {
+            let path = format!("m/0/{}", i);
+            let derivation = DerivationPath::from_str(&path).expect("Valid path");
+            let key = xpub.derive_pub(&secp, &derivation)?.to_pub();
+            addresses.push(format!(
+                "{:<8}{}",
+                path,
+                Address::p2pkh(&key, Network::Bitcoin)
+            ));
+            addresses.push(format!(
+                "{:<8}{}",
+                path,
+                Address::p2shwpkh(&key, Network::Bitcoin)?
+            ));
+            addresses.push(format!(
+                "{:<8}{}",
+                path,
+                Address::p2wpkh(&key, Network::Bitcoin)?
+            ));
+        }
+        Ok(addresses)
+    }
+
+    /// Derives the master XPRV and the private key for this address from a found seed
+    pub fn export_wallet(&self, seed: &str, passphrase: &str) -> Result<Vec<String>> {
+        let master = Self::master(seed, passphrase)?;
+        let mut lines = vec![format!("Master XPRV: {}", master)];
+        if self.kind.is_xpub {
+            return Ok(lines);
+        }
+
+        let (derivation, key) = self.derive_key(&master)?;
+        lines.push(format!("Address Path: {}", derivation));
+        lines.push(format!("Address WIF:  {}", key.to_wif()));
+        Ok(lines)
+    }
+
+    /// Finds which of the derivation paths produced the address from a found seed
+    pub fn find_derivation(&self, seed: &str, passphrase: &str) -> Result<Option<String>> {
+        let master = Self::master(seed, passphrase)?;
+        if self.kind.is_xpub {
+            let xpub = Xpub::from_priv(&Secp256k1::new(), &master);
+            if xpub.to_string() != self.formatted {
+                bail!("The master XPUB does not match '{}'", self.formatted);
+            }
+            return Ok(None);
+        }
+        let (derivation, _) = self.derive_key(&master)?;
+        Ok(Some(derivation))
+    }
+
+    fn master(seed: &str, passphrase: &str) -> Result<Xpriv> {
+        let mnemonic = seed.replace(SEPARATOR, " ");
+        Ok(Xpriv::new_master(
+            Network::Bitcoin,
+            &bip39_seed(&mnemonic, &decode_hex_plain(passphrase)),
+        )?)
+    }
+
+    fn derive_key(&self, master: &Xpriv) -> Result<(String, PrivateKey)> {
+        let secp = Secp256k1::new();
+        for derivation in &self.derivations.derivations {
+            let path = DerivationPath::from_str(derivation)?;
+            let key = master.derive_priv(&secp, &path)?.to_priv();
+            if self.address_of(&key.public_key(&secp))? == self.formatted {
+                return Ok((derivation.clone(), key));
+            }
+        }
+        bail!(
+            "No derivation path matches the address '{}'",
+            self.formatted
+        )
+    }
+
+    fn address_of(&self, key: &PublicKey) -> Result<String> {
+        let address = match self.kind.key.as_str() {
+            "P2PKH" => Address::p2pkh(key, Network::Bitcoin),
+            "P2SH-P2WPKH" => Address::p2shwpkh(key, Network::Bitcoin)?,
+            "P2WPKH" => Address::p2wpkh(key, Network::Bitcoin)?,
+            kind => bail!("Cannot derive a '{}' address", kind),
+        };
+        Ok(address.to_string())
+    }
+
+    /// Converts a raw HASH160 in hex into the address of every kind it could be
+    /// (a pubkey hash for 'P2PKH' and 'P2WPKH', a script hash for 'P2SH-P2WPKH')
+    pub fn from_hash160_any(hash160: &str, derivation: &Option<String>) -> Result<Vec<Self>> {
+        let mut addresses = vec![];
+        for kind in ["P2PKH", "P2SH-P2WPKH", "P2WPKH"] {
+            let address = Self::from_hash160(hash160, kind)?;
+            addresses.push(Self::from_arg(&address, derivation)?);
+        }
+        Ok(addresses)
+    }
+
+    /// Converts a raw HASH160 in hex into the address of the given kind
+    pub fn from_hash160(hash160: &str, kind: &str) -> Result<String> {
+        let script = match kind {
+            "P2PKH" => PubkeyHash::from_str(hash160).map(|h| ScriptBuf::new_p2pkh(&h)),
+            "P2SH-P2WPKH" => ScriptHash::from_str(hash160).map(|h| ScriptBuf::new_p2sh(&h)),
+            "P2WPKH" => WPubkeyHash::from_str(hash160).map(|h| ScriptBuf::new_p2wpkh(&h)),
+            _ => bail!(
+                "Address type '{}' must be one of 'P2PKH', 'P2SH-P2WPKH', 'P2WPKH'",
+                kind
+            ),
+        };
+        let err = format_err!("HASH160 '{}' should be 40 hex characters", hash160);
+        let script = script.map_err(|_| err)?;
+        Self::from_script_pubkey(&script.to_hex_string())
+    }
+
+    /// Converts a raw scriptPubKey in hex into its address, inferring the script type
+    pub fn from_script_pubkey(script: &str) -> Result<String> {
+        let err = format_err!("ScriptPubKey '{}' is not valid hex", script);
+        let script = ScriptBuf::from_hex(script).map_err(|_| err)?;
+        match Address::from_script(&script, Network::Bitcoin) {
+            Ok(address) => Ok(address.to_string()),
+            Err(_) => bail!("ScriptPubKey '{}' is not a standard address script", script),
+        }
+    }
+
     fn kind(address: &str) -> Result<AddressKind> {
         let strs: Vec<_> = address_kinds().iter().map(|k| format!("\t{}", k)).collect();
         let error = format!("You must use one of the following formats (https://en.bitcoin.it/wiki/List_of_address_prefixes)\n{}", strs.join("\n"));
@@ -87,7 +398,7 @@ impl AddressValid {
                     match Xpub::from_str(&address) {
                         Ok(xpub) if is_master(xpub) => return Ok(kind.clone()),
                         Ok(_) => bail!(
-                            "Xpub is not a master public key (use an address instead)\n{}",
+                            "Xpub is not a master public key, use an address or pass the account path e.g. --derivation \"m/84'/0'/0'\"\n{}",
                             error
                         ),
                         Err(_) => bail!("Xpub is not correctly encoded\n{}", error),
@@ -111,16 +422,7 @@ impl AddressValid {
     ) -> Result<Derivations> {
         let split = match arg {
             None => kind.derivations.clone(),
-            Some(arg) => {
-                let args = if arg.contains(",") {
-                    arg.split(",")
-                } else if arg.contains("|") {
-                    arg.split("|")
-                } else {
-                    arg.split(" ")
-                };
-                args.map(|s| s.to_string()).collect()
-            }
+            Some(arg) => Self::split_derivations(arg),
         };
 
         let mut derivations = vec![];
@@ -149,6 +451,25 @@ impl AddressValid {
         Ok(Derivations { derivations, args })
     }
 
+    /// Every path is an alternative so `,` `|` and whitespace all separate paths (except inside `{}`)
+    fn split_derivations(arg: &str) -> Vec<String> {
+        let mut paths = vec![String::new()];
+        let mut in_set = false;
+        for c in arg.chars() {
+            match c {
+                '{' => in_set = true,
+                '}' => in_set = false,
+                _ => {}
+            }
+            if !in_set && (c == ',' || c == '|' || c.is_whitespace()) {
+                paths.push(String::new());
+            } else if !c.is_whitespace() {
+                paths.last_mut().expect("not empty").push(c);
+            }
+        }
+        paths.into_iter().filter(|s| !s.is_empty()).collect()
+    }
+
     fn derivation_paths(
         derivation: &str,
         num_args: usize,
@@ -157,6 +478,15 @@ impl AddressValid {
         let mut derivations = vec!["m".to_string()];
         let mut args = vec!["m".to_string()];
 
+        let depth = derivation.split("/").count();
+        if depth > MAX_DERIVATION_DEPTH {
+            bail!(
+                "Derivation path 'm/{}' is {} levels deep but BIP32 allows at most {}",
+                derivation,
+                depth,
+                MAX_DERIVATION_DEPTH
+            );
+        }
         for path in derivation.split("/").into_iter() {
             let nodes = Self::derivation_nodes(path).map_err(|err| {
                 format_err!(
@@ -169,12 +499,17 @@ impl AddressValid {
 
             derivations = Self::extend_paths(&derivations, &nodes, "/");
 
-            if num_args + derivations.len() > max_derivations {
+            // hashcat only understands '?' so sets are always exploded
+            if num_args + derivations.len() > max_derivations || path.contains('{') {
                 args = Self::extend_paths(&args, &nodes, "/");
             } else {
                 args = Self::extend_paths(&args, &vec![path.to_string()], "/");
             }
         }
+        // exploded sets still fit in a single hash line
+        if derivation.contains('{') && num_args + derivations.len() <= max_derivations {
+            args = vec![args.join(",")];
+        }
 
         return Ok((derivations, args));
     }
@@ -200,13 +535,60 @@ impl AddressValid {
         if path.starts_with("?") {
             question = node.next().unwrap().to_string();
         }
+        if let Some(set) = node.as_str().strip_prefix('{') {
+            let set = set
+                .strip_suffix('}')
+                .ok_or(format_err!("unclosed set '{}'", path))?;
+            let mut nodes = vec![];
+            for num in set.split([',', '|']).filter(|num| !num.is_empty()) {
+                let num = Self::derivation_index(num, !suffix.is_empty())?;
+                nodes.push(format!("{}{}", num, suffix));
+            }
+            if nodes.is_empty() || !question.is_empty() {
+                bail!("invalid set '{}'", path);
+            }
+            return Ok(nodes);
+        }
 
-        return match node.as_str().parse::<u32>() {
-            Ok(num) if question.is_empty() => Ok(vec![format!("{}{}", num, suffix)]),
-            Ok(num) => Ok((0..=num).map(|i| format!("{}{}", i, suffix)).collect()),
-            Err(_) => bail!("invalid number '{}'", node.as_str()),
+        let num = Self::derivation_index(node.as_str(), !suffix.is_empty())?;
+        return match question.is_empty() {
+            true => Ok(vec![format!("{}{}", num, suffix)]),
+            false => Ok((0..=num).map(|i| format!("{}{}", i, suffix)).collect()),
         };
     }
+
+    fn derivation_index(num: &str, hardened: bool) -> Result<u32> {
+        match num.trim().parse::<u32>() {
+            Ok(num) if num <= MAX_DERIVATION_INDEX => Ok(num),
+            Ok(num) if hardened => bail!(
+                "hardened index '{}h' must be between 0h and {}h",
+                num,
+                MAX_DERIVATION_INDEX
+            ),
+            Ok(num) => bail!(
+                "index '{}' is over the BIP32 maximum of {} (use a hardened index like '0h' instead)",
+                num,
+                MAX_DERIVATION_INDEX
+            ),
+            Err(_) => bail!("invalid number '{}'", num),
+        }
+    }
+}
+
+/// BIP39 seed from the mnemonic using PBKDF2-HMAC-SHA512
+fn bip39_seed(mnemonic: &str, passphrase: &[u8]) -> [u8; 64] {
+    let hmac = |data: &[&[u8]]| {
+        let mut engine = HmacEngine::<sha512::Hash>::new(mnemonic.as_bytes());
+        data.iter().for_each(|bytes| engine.input(bytes));
+        Hmac::from_engine(engine).to_byte_array()
+    };
+    let mut block = hmac(&[b"mnemonic", passphrase, &1_u32.to_be_bytes()]);
+    let mut seed = block;
+    for _ in 1..BIP39_PBKDF2_ROUNDS {
+        block = hmac(&[&block]);
+        seed.iter_mut().zip(block.iter()).for_each(|(s, b)| *s ^= b);
+    }
+    seed
 }
 
 pub fn address_kinds() -> Vec<AddressKind> {
@@ -242,6 +624,16 @@ pub fn address_kinds() -> Vec<AddressKind> {
     ]
 }
 
+/// The address type derived by the standard path of a BIP
+fn bip_kind(bip: u32) -> Option<&'static str> {
+    match bip {
+        44 => Some("P2PKH"),
+        49 => Some("P2SH-P2WPKH"),
+        84 => Some("P2WPKH"),
+        _ => None,
+    }
+}
+
 fn is_master(xpub: Xpub) -> bool {
     return xpub.network == Network::Bitcoin
         && xpub.depth == 0
@@ -296,11 +688,217 @@ mod tests {
         let kind = AddressValid::kind("xpub661MyMwAqRbcG95rS28rhZiknMvbUJhPpEWgMUbWa4xjMEc12aVewXf7fey3rGD9Sef81NXqTd1vyYToRokkiU9BTz6u5UXmikfNHTV9oCT").unwrap();
         assert_eq!(kind.key, "XPUB");
 
+        // clipboard whitespace and uppercase bech32
+        let derivation = Some("m/0/0".to_string());
+        let address = " bc1q3zn9axe5k3tptupymypjzheuxf8r9yp7zutulg\n";
+        let valid = AddressValid::from_arg(address, &derivation).unwrap();
+        assert_eq!(
+            valid.formatted,
+            "bc1q3zn9axe5k3tptupymypjzheuxf8r9yp7zutulg"
+        );
+        let address = "BC1Q3ZN9AXE5K3TPTUPYMYPJZHEUXF8R9YP7ZUTULG";
+        let valid = AddressValid::from_arg(address, &derivation).unwrap();
+        assert_eq!(
+            valid.formatted,
+            "bc1q3zn9axe5k3tptupymypjzheuxf8r9yp7zutulg"
+        );
+        let address = "BC1Q3ZN9AXE5K3tptupymypjzheuxf8r9yp7zutulg";
+        let err = AddressValid::from_arg(address, &derivation).unwrap_err();
+        assert!(err.to_string().contains("mix uppercase and lowercase"));
+        let address = " 1111111111111111111114oLvT2 ";
+        let valid = AddressValid::from_arg(address, &derivation).unwrap();
+        assert_eq!(valid.formatted, "1111111111111111111114oLvT2");
+
         // non-master xpub
         let kind = AddressValid::kind("xpub6878MZDSpciXuNC2cRRBa6dZsgBeE8UYaFDqA1uTazMaYdR1Xq7HFHBC3FpcFHiMytkmrMVBQKi3Wx2wT9xAn8mxuMeqtJG8TPDcpyfTk2J");
         assert!(kind.is_err());
     }
 
+    #[test]
+    fn includes_change_addresses() {
+        let address = AddressValid::from_arg("1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9", &None).unwrap();
+        let change = address.with_change().unwrap();
+        assert_eq!(change.derivations.total(), 4);
+        assert_eq!(
+            change.derivations.args(),
+            vec!["m/0/0,m/44'/0'/0'/0/0,m/1/0,m/44'/0'/0'/1/0"]
+        );
+        let ranged = change.with_address_range("0-1").unwrap();
+        assert_eq!(ranged.derivations.total(), 8);
+        assert_eq!(ranged.derivations.end(), "m/44'/0'/0'/1/1");
+
+        let derivation = Some("m/0".to_string());
+        let address = AddressValid::from_arg("1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9", &derivation);
+        assert!(address.unwrap().with_change().is_err());
+    }
+
+    #[test]
+    fn expands_address_ranges() {
+        let address = AddressValid::from_arg("1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9", &None).unwrap();
+        let ranged = address.with_address_range("0-100").unwrap();
+        assert_eq!(ranged.derivations.total(), 202);
+        assert_eq!(ranged.derivations.begin(), "m/0/0");
+        assert_eq!(ranged.derivations.end(), "m/44'/0'/0'/0/100");
+
+        let derivation = Some("m/0/0".to_string());
+        let address = AddressValid::from_arg("1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9", &derivation);
+        let ranged = address.unwrap().with_address_range("5-6").unwrap();
+        assert_eq!(ranged.derivations.args(), vec!["m/0/5,m/0/6"]);
+
+        assert!(ranged.with_address_range("6-5").is_err());
+        assert!(ranged.with_address_range("0").is_err());
+        let xpub = "xpub661MyMwAqRbcG95rS28rhZiknMvbUJhPpEWgMUbWa4xjMEc12aVewXf7fey3rGD9Sef81NXqTd1vyYToRokkiU9BTz6u5UXmikfNHTV9oCT";
+        let xpub = AddressValid::from_arg(xpub, &None).unwrap();
+        assert!(xpub.with_address_range("0-100").is_err());
+    }
+
+    #[test]
+    fn derives_xpub_addresses() {
+        let xpub = "xpub661MyMwAqRbcG95rS28rhZiknMvbUJhPpEWgMUbWa4xjMEc12aVewXf7fey3rGD9Sef81NXqTd1vyYToRokkiU9BTz6u5UXmikfNHTV9oCT";
+        let address = AddressValid::from_arg(xpub, &None).unwrap();
+        let addresses = address.xpub_addresses(2).unwrap();
+        assert_eq!(addresses.len(), 6);
+        assert!(addresses[0].starts_with("m/0/0   1"));
+        assert!(addresses[1].starts_with("m/0/0   3"));
+        assert!(addresses[2].starts_with("m/0/0   bc1q"));
+        assert!(addresses[5].starts_with("m/0/1   bc1q"));
+
+        let address = AddressValid::from_arg("1111111111111111111114oLvT2", &None).unwrap();
+        assert!(address.xpub_addresses(2).is_err());
+    }
+
+    #[test]
+    fn converts_raw_scripts() {
+        let hash = "0000000000000000000000000000000000000000";
+        let address = AddressValid::from_hash160(hash, "P2PKH").unwrap();
+        assert_eq!(address, "1111111111111111111114oLvT2");
+        let address = AddressValid::from_hash160(hash, "P2SH-P2WPKH").unwrap();
+        assert_eq!(address, "31h1vYVSYuKP6AhS86fbRdMw9XHieotbST");
+        let address = AddressValid::from_hash160(hash, "P2WPKH").unwrap();
+        assert_eq!(address, "bc1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq9e75rs");
+        assert!(AddressValid::from_hash160("00", "P2PKH").is_err());
+        assert!(AddressValid::from_hash160(hash, "P2TR").is_err());
+
+        let addresses = AddressValid::from_hash160_any(hash, &None).unwrap();
+        let kinds: Vec<_> = addresses.iter().map(|a| a.kind.key.as_str()).collect();
+        assert_eq!(kinds, vec!["P2PKH", "P2SH-P2WPKH", "P2WPKH"]);
+        assert_eq!(addresses[1].formatted, "31h1vYVSYuKP6AhS86fbRdMw9XHieotbST");
+        assert!(AddressValid::from_hash160_any("00", &None).is_err());
+
+        let script = format!("76a914{}88ac", hash);
+        let address = AddressValid::from_script_pubkey(&script).unwrap();
+        assert_eq!(address, "1111111111111111111114oLvT2");
+        let address = AddressValid::from_script_pubkey(&format!("0014{}", hash)).unwrap();
+        assert_eq!(address, "bc1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq9e75rs");
+        assert!(AddressValid::from_script_pubkey("zz").is_err());
+        assert!(AddressValid::from_script_pubkey("6a").is_err());
+    }
+
+    #[test]
+    fn exports_wallets() {
+        let seed = "abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,about";
+        let xprv = "xprv9s21ZrQH143K3h3fDYiay8mocZ3afhfULfb5GX8kCBdno77K4HiA15Tg23wpbeF1pLfs1c5SPmYHrEpTuuRhxMwvKDwqdKiGJS9XFKzUsAF";
+        let xpub = Xpub::from_priv(&Secp256k1::new(), &Xpriv::from_str(xprv).unwrap());
+        let address = AddressValid::from_arg(&xpub.to_string(), &None).unwrap();
+        let wallet = address.export_wallet(seed, "TREZOR").unwrap();
+        assert_eq!(wallet, vec![format!("Master XPRV: {}", xprv)]);
+        assert_eq!(address.find_derivation(seed, "TREZOR").unwrap(), None);
+        assert!(address.find_derivation(seed, "").is_err());
+
+        let derivation = Some("m/0/0,m/84h/0h/0h/0/0".to_string());
+        let address =
+            AddressValid::from_arg("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu", &derivation)
+                .unwrap();
+        let wallet = address.export_wallet(seed, "").unwrap();
+        assert_eq!(wallet[1], "Address Path: m/84h/0h/0h/0/0");
+        assert_eq!(
+            wallet[2],
+            "Address WIF:  KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d"
+        );
+
+        let path = address.find_derivation(seed, "").unwrap();
+        assert_eq!(path, Some("m/84h/0h/0h/0/0".to_string()));
+
+        let address = AddressValid::from_arg("1111111111111111111114oLvT2", &derivation).unwrap();
+        assert!(address.export_wallet(seed, "").is_err());
+        assert!(address.find_derivation(seed, "").is_err());
+
+        // hashcat prints non-printable passphrases as $HEX[...]
+        let seed = "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn";
+        let derivation = Some("m/44'/0'/0'/0/0".to_string());
+        let address = AddressValid::from_arg("1ftB12snqQT77KrugvrYVSZTKku7UipZg", &derivation);
+        let address = address.unwrap();
+        let path = address.find_derivation(seed, "$HEX[00ff41]").unwrap();
+        assert_eq!(path, Some("m/44'/0'/0'/0/0".to_string()));
+        let wallet = address.export_wallet(seed, "$HEX[00ff41]").unwrap();
+        assert_eq!(wallet[1], "Address Path: m/44'/0'/0'/0/0");
+        assert!(address.export_wallet(seed, "00ff41").is_err());
+    }
+
+    #[test]
+    fn derives_bip_paths() {
+        let path = AddressValid::bip_derivation(84, 0, 0).unwrap();
+        assert_eq!(path, "m/84'/0'/0'/0/0");
+        let path = AddressValid::bip_derivation(44, 1, 5).unwrap();
+        assert_eq!(path, "m/44'/0'/1'/0/5");
+        assert!(AddressValid::bip_derivation(86, 0, 0).is_err());
+        assert!(AddressValid::bip_derivation(32, 0, 0).is_err());
+
+        let derivation = Some(AddressValid::bip_derivation(84, 0, 0).unwrap());
+        let address = "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu";
+        let address = AddressValid::from_arg(address, &derivation).unwrap();
+        assert!(address.is_bip(84));
+        assert!(!address.is_bip(44));
+    }
+
+    #[test]
+    fn identifies_master_xpubs() {
+        let xprv = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+        let secp = Secp256k1::new();
+        let master = Xpriv::from_str(xprv).unwrap();
+        let xpub = Xpub::from_priv(&secp, &master);
+        assert!(is_master(xpub));
+        let address = AddressValid::from_arg(&xpub.to_string(), &None).unwrap();
+        assert!(address.kind.is_xpub);
+        assert_eq!(address.derivations.total(), 1);
+        assert!(address.find_derivation(&"zoo ".repeat(11), "").is_err());
+
+        let account = DerivationPath::from_str("m/84h/0h/0h").unwrap();
+        let account = Xpub::from_priv(&secp, &master.derive_priv(&secp, &account).unwrap());
+        assert!(!is_master(account));
+        let child = DerivationPath::from_str("m/0").unwrap();
+        let child = Xpub::from_priv(&secp, &master.derive_priv(&secp, &child).unwrap());
+        assert!(!is_master(child));
+        assert!(AddressValid::kind(&child.to_string()).is_err());
+        let derivation = Some("m/0/0".to_string());
+        assert!(AddressValid::from_arg(&xpub.to_string(), &derivation).is_err());
+    }
+
+    #[test]
+    fn accepts_account_xpubs() {
+        let xprv = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+        let secp = Secp256k1::new();
+        let master = Xpriv::from_str(xprv).unwrap();
+        let account = DerivationPath::from_str("m/84h/0h/0h").unwrap();
+        let xpub = Xpub::from_priv(&secp, &master.derive_priv(&secp, &account).unwrap());
+        let derivation = Some("m/84'/0'/0'".to_string());
+        let address = AddressValid::from_arg(&xpub.to_string(), &derivation).unwrap();
+        assert_eq!(address.kind.key, "P2WPKH");
+        assert_eq!(
+            address.formatted,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(address.derivations.args(), vec!["m/84'/0'/0'/0/0"]);
+
+        let wrong_depth = Some("m/84'/0'".to_string());
+        assert!(AddressValid::from_arg(&xpub.to_string(), &wrong_depth).is_err());
+        let wrong_account = Some("m/84'/0'/1'".to_string());
+        assert!(AddressValid::from_arg(&xpub.to_string(), &wrong_account).is_err());
+        let wrong_purpose = Some("m/86'/0'/0'".to_string());
+        assert!(AddressValid::from_arg(&xpub.to_string(), &wrong_purpose).is_err());
+        assert!(AddressValid::from_arg(&xpub.to_string(), &None).is_err());
+    }
+
     #[test]
     fn parses_derivations() {
         let kind = AddressKind::new("", "", "", vec!["m/123".to_string()], false);
@@ -317,9 +915,47 @@ mod tests {
         assert_eq!(derivation.end(), "m/1/2");
         assert_eq!(derivation.total(), 4);
         assert_eq!(derivation.hash_ratio(), 4.0);
+        assert_eq!(derivation.batches(), vec![("m/0,m/1/?2".to_string(), 4)]);
 
         assert!(AddressValid::derivation(&kind, &Some("z/?2".to_string()), 1).is_err());
 
+        // mixed separators
+        let mixed = AddressValid::derivation(&kind, &Some("m/0|m/1,m/2".to_string()), 1).unwrap();
+        assert_eq!(mixed.args(), vec!["m/0", "m/1", "m/2"]);
+        let mixed = AddressValid::derivation(&kind, &Some(" m/0, m/1 |m/2 ".to_string()), 1);
+        assert_eq!(mixed.unwrap().args(), vec!["m/0", "m/1", "m/2"]);
+        assert!(AddressValid::derivation(&kind, &Some("m/0,0/1".to_string()), 1).is_err());
+
+        // sets of specific indices
+        let set = AddressValid::derivation(&kind, &Some("m/0/{0,5|9}/0 m/1".to_string()), 10);
+        let set = set.unwrap();
+        assert_eq!(set.args(), vec!["m/0/0/0,m/0/5/0,m/0/9/0,m/1"]);
+        assert_eq!(set.total(), 4);
+        let set = AddressValid::derivation(&kind, &Some("m/{1, 2}h".to_string()), 10).unwrap();
+        assert_eq!(set.args(), vec!["m/1h,m/2h"]);
+        assert!(AddressValid::derivation(&kind, &Some("m/{1,2".to_string()), 10).is_err());
+        assert!(AddressValid::derivation(&kind, &Some("m/{}".to_string()), 10).is_err());
+        assert!(AddressValid::derivation(&kind, &Some("m/?{1}".to_string()), 10).is_err());
+
+        // BIP32 limits
+        let max = AddressValid::derivation(&kind, &Some("m/2147483647h".to_string()), 1);
+        assert_eq!(max.unwrap().args(), vec!["m/2147483647h"]);
+        let over = AddressValid::derivation(&kind, &Some("m/2147483648h".to_string()), 1);
+        let err = over.err().unwrap().to_string();
+        assert!(err.contains("must be between 0h and 2147483647h"));
+        assert!(!err.contains("use a hardened index"));
+        let over = AddressValid::derivation(&kind, &Some("m/2147483648".to_string()), 1);
+        let err = over.err().unwrap().to_string();
+        assert!(err.contains("BIP32 maximum"));
+        assert!(err.contains("use a hardened index like '0h'"));
+        assert!(
+            AddressValid::derivation(&kind, &Some("m/{1,2147483648}".to_string()), 10).is_err()
+        );
+        let deep = format!("m{}", "/0".repeat(255));
+        assert!(AddressValid::derivation(&kind, &Some(deep.clone()), 1).is_ok());
+        let deep = AddressValid::derivation(&kind, &Some(format!("{}/0", deep)), 1);
+        assert!(deep.err().unwrap().to_string().contains("256 levels deep"));
+
         // splits if over 10
         let derivation =
             AddressValid::derivation(&kind, &Some("m/?9'/9/?9|m/0/0".to_string()), 10).unwrap();
@@ -343,5 +979,8 @@ mod tests {
                 "m/0/0",
             ]
         );
+        let batches = derivation.batches();
+        assert_eq!(batches[0], ("m/?9'/9/0".to_string(), 10));
+        assert_eq!(batches[10], ("m/0/0".to_string(), 1));
     }
 }
@@ -0,0 +1,187 @@
+use std::io;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Processes that are still running so they can be killed if seedcat is interrupted
+static RUNNING: Mutex<Vec<(usize, Box<dyn HashcatProcess>)>> = Mutex::new(vec![]);
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Spawns the hashcat process, abstracted so tests can run without a GPU
+pub trait HashcatBackend: Send + Sync {
+    fn spawn(&self, cmd: &mut Command) -> io::Result<Box<dyn HashcatProcess>>;
+}
+
+/// A running hashcat process with piped input and output
+pub trait HashcatProcess: Send {
+    fn take_stdin(&mut self) -> Option<Box<dyn Write + Send + Sync>>;
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send + Sync>>;
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send + Sync>>;
+    fn kill(&mut self) -> io::Result<()>;
+    /// Waits for the process to exit returning its exit code (None if killed by a signal)
+    fn wait(&mut self) -> io::Result<Option<i32>>;
+}
+
+/// Runs the real hashcat executable as a child process
+#[derive(Debug, Clone)]
+pub struct ProcessBackend;
+
+impl HashcatBackend for ProcessBackend {
+    fn spawn(&self, cmd: &mut Command) -> io::Result<Box<dyn HashcatProcess>> {
+        let child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        Ok(Box::new(child))
+    }
+}
+
+impl HashcatProcess for Child {
+    fn take_stdin(&mut self) -> Option<Box<dyn Write + Send + Sync>> {
+        self.stdin
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Write + Send + Sync>)
+    }
+
+    fn take_stdout(&mut self) -> Option<Box<dyn Read + Send + Sync>> {
+        self.stdout
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send + Sync>)
+    }
+
+    fn take_stderr(&mut self) -> Option<Box<dyn Read + Send + Sync>> {
+        self.stderr
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send + Sync>)
+    }
+
+    fn kill(&mut self) -> io::Result<()> {
+        Child::kill(self)
+    }
+
+    fn wait(&mut self) -> io::Result<Option<i32>> {
+        Ok(Child::wait(self)?.code())
+    }
+}
+
+/// A registered process that is killed when dropped or when seedcat is interrupted
+pub struct Running {
+    id: usize,
+}
+
+impl Running {
+    pub fn new(process: Box<dyn HashcatProcess>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        RUNNING.lock().expect("not poisoned").push((id, process));
+        Self { id }
+    }
+
+    pub fn kill(mut self) -> io::Result<()> {
+        self.take().map_or(Ok(()), |mut process| process.kill())
+    }
+
+    /// Waits for a process that has closed its output to exit
+    pub fn wait(mut self) -> io::Result<Option<i32>> {
+        self.take().map_or(Ok(None), |mut process| process.wait())
+    }
+
+    fn take(&mut self) -> Option<Box<dyn HashcatProcess>> {
+        let mut running = RUNNING.lock().expect("not poisoned");
+        let index = running.iter().position(|(id, _)| *id == self.id)?;
+        Some(running.remove(index).1)
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if let Some(mut process) = self.take() {
+            // the process may have already exited
+            let _ = process.kill();
+        }
+    }
+}
+
+/// Kills every registered process
+pub fn kill_running() {
+    let running: Vec<_> = RUNNING.lock().expect("not poisoned").drain(..).collect();
+    for (_, mut process) in running {
+        let _ = process.kill();
+    }
+}
+
+/// Mock backend for testing without hashcat
+#[cfg(test)]
+pub mod mock {
+    use std::io;
+    use std::io::{Read, Write};
+    use std::process::Command;
+
+    use crate::backend::{HashcatBackend, HashcatProcess};
+
+    /// Returns canned stdout and stderr instead of running hashcat
+    #[derive(Debug, Clone)]
+    pub struct MockBackend {
+        stdout: String,
+        stderr: String,
+        code: i32,
+    }
+
+    impl MockBackend {
+        pub fn new(stdout: &str, stderr: &str) -> Self {
+            Self {
+                stdout: stdout.to_string(),
+                stderr: stderr.to_string(),
+                // hashcat exits with 1 when exhausted
+                code: 1,
+            }
+        }
+
+        pub fn with_code(&self, code: i32) -> Self {
+            let mut copy = self.clone();
+            copy.code = code;
+            copy
+        }
+    }
+
+    impl HashcatBackend for MockBackend {
+        fn spawn(&self, _: &mut Command) -> io::Result<Box<dyn HashcatProcess>> {
+            Ok(Box::new(MockProcess {
+                stdout: Some(self.stdout.clone().into_bytes()),
+                stderr: Some(self.stderr.clone().into_bytes()),
+                code: self.code,
+            }))
+        }
+    }
+
+    struct MockProcess {
+        stdout: Option<Vec<u8>>,
+        stderr: Option<Vec<u8>>,
+        code: i32,
+    }
+
+    impl HashcatProcess for MockProcess {
+        fn take_stdin(&mut self) -> Option<Box<dyn Write + Send + Sync>> {
+            Some(Box::new(io::sink()))
+        }
+
+        fn take_stdout(&mut self) -> Option<Box<dyn Read + Send + Sync>> {
+            let stdout = self.stdout.take()?;
+            Some(Box::new(io::Cursor::new(stdout)))
+        }
+
+        fn take_stderr(&mut self) -> Option<Box<dyn Read + Send + Sync>> {
+            let stderr = self.stderr.take()?;
+            Some(Box::new(io::Cursor::new(stderr)))
+        }
+
+        fn kill(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn wait(&mut self) -> io::Result<Option<i32>> {
+            Ok(Some(self.code))
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::fs::File;
@@ -7,7 +7,7 @@ use std::io::{BufWriter, Write};
 use anyhow::{format_err, Error, Result};
 use gzp::deflate::Gzip;
 use gzp::par::compress::{ParCompress, ParCompressBuilder};
-use gzp::ZWriter;
+use gzp::{Compression, ZWriter};
 
 use crate::logger::Logger;
 use crate::permutations::Permutations;
@@ -63,6 +63,19 @@ impl<T: Clone + Debug> Combinations<T> {
         }
     }
 
+    /// Permutes in order of closeness to the original element order, cannot shard permutations
+    pub fn closest_first(&self) -> Self {
+        let mut permutations = self.permutations.closest_first();
+        let permutation = permutations.next().unwrap_or(&vec![]).clone();
+        Self::new_shard(
+            self.elements.clone(),
+            permutations,
+            self.permute_indices.clone(),
+            self.length,
+            permutation,
+        )
+    }
+
     /// Returns Some(element) if the position is fixed, otherwise None
     pub fn fixed_positions(&self) -> Vec<Option<T>> {
         let mut fixed = vec![];
@@ -111,6 +124,7 @@ impl<T: Clone + Debug> Combinations<T> {
     }
 
     /// Returns an estimate of the total for a given sample size
+    /// Samples the first permutations in a fixed order so the estimate is reproducible across runs
     pub fn estimate_total(&self, sample_size: u64) -> u64 {
         let mut total_combo = 1_u64;
         let mut total_perm = 0_u64;
@@ -129,8 +143,11 @@ impl<T: Clone + Debug> Combinations<T> {
         }
 
         let mut count = 0;
-        let mut permutations = Permutations::new(sizes, self.permutation.len());
-        let num_permutations = self.permutations() as f64;
+        // only count the permutations in this slice
+        let range = self.permutations.range();
+        let num_permutations = (range.end - range.start) as f64;
+        let k = self.permutation.len();
+        let mut permutations = Permutations::new_shard(sizes, k, range.start, range.end);
         while let Some(next) = permutations.next() {
             count += 1;
             total_perm = total_perm.saturating_add(next.iter().product());
@@ -154,6 +171,11 @@ impl<T: Clone + Debug> Combinations<T> {
         permutations
     }
 
+    /// Returns the number of indices being permuted
+    pub fn permuted_len(&self) -> usize {
+        self.permute_indices.len()
+    }
+
     /// Returns the length of the output
     pub fn len(&self) -> usize {
         self.length
@@ -225,12 +247,13 @@ impl<T: Clone + Debug> Combinations<T> {
         Some(&self.next)
     }
 
-    // Splits combinations into a minimum number of shards
+    // Splits combinations into a minimum number of shards with similar amounts of work
     pub fn shard(&self, num: usize) -> Vec<Combinations<T>> {
         let mut shards = vec![];
 
-        if self.permutations.len() > 1 {
-            let perm_shards = min(num as u64, self.permutations.len()) as usize;
+        let range = self.permutations.range();
+        if range.end - range.start > 1 {
+            let perm_shards = min(num as u64, range.end - range.start) as usize;
             for mut perm in self.permutations.shard(perm_shards) {
                 let permutation = perm.next().unwrap_or(&vec![]).clone();
                 shards.push(Self::new_shard(
@@ -245,61 +268,162 @@ impl<T: Clone + Debug> Combinations<T> {
             shards.push(self.clone());
         }
 
-        for i in 0..self.elements.len() {
-            if !self.permute_indices.contains(&i) {
-                shards = Self::shard_index(shards, i);
-                if shards.len() >= num {
-                    break;
+        // Repeatedly subdivide the largest shard so no shard is left with most of the work
+        while shards.len() < num {
+            let mut largest = 0;
+            for i in 0..shards.len() {
+                if shards[i].shard_weight() > shards[largest].shard_weight() {
+                    largest = i;
                 }
             }
+            let Some(index) = shards[largest].split_index() else {
+                break;
+            };
+            let pieces = min(
+                shards[largest].elements[index].len(),
+                num - shards.len() + 1,
+            );
+            let split = shards[largest].split(index, pieces);
+            shards.splice(largest..=largest, split);
         }
 
         shards
     }
 
-    fn shard_index(shards: Vec<Combinations<T>>, index: usize) -> Vec<Combinations<T>> {
-        let mut next_shards = vec![];
-        for s in shards {
-            for choice in &s.elements[index] {
-                let mut elements = s.elements.clone();
-                elements[index] = vec![choice.clone()];
-                let ns = Self::new_shard(
-                    elements,
-                    s.permutations.clone(),
-                    s.permute_indices.clone(),
-                    s.length,
-                    s.permutation.clone(),
-                );
-                next_shards.push(ns);
+    /// Approximate combinations in a shard, assuming permutations have similar combinations
+    fn shard_weight(&self) -> u64 {
+        let mut weight = max(self.permutations.len(), 1);
+        for i in 0..self.elements.len() {
+            if !self.permute_indices.contains(&i) {
+                weight = weight.saturating_mul(self.elements[i].len() as u64);
             }
         }
-        next_shards
+        weight
+    }
+
+    /// Returns the `index` of `num` slices of near equal size, or None if there are too few choices
+    pub fn slice(&self, num: usize, index: usize) -> Option<Combinations<T>> {
+        let range = self.permutations.range();
+        if range.end - range.start > 1 && range.end - range.start >= num as u64 {
+            let mut perm = self.permutations.slice(num, index);
+            let permutation = perm.next().unwrap_or(&vec![]).clone();
+            return Some(Self::new_shard(
+                self.elements.clone(),
+                perm,
+                self.permute_indices.clone(),
+                self.length,
+                permutation,
+            ));
+        }
+        let split_index = self.split_index()?;
+        if self.elements[split_index].len() < num {
+            return None;
+        }
+        self.split(split_index, num).into_iter().nth(index)
+    }
+
+    /// The first non-permuted index with the most elements, if any can be split
+    fn split_index(&self) -> Option<usize> {
+        let mut index = None;
+        let mut most = 1;
+        for i in 0..self.elements.len() {
+            if !self.permute_indices.contains(&i) && self.elements[i].len() > most {
+                most = self.elements[i].len();
+                index = Some(i);
+            }
+        }
+        index
+    }
+
+    /// Splits the elements at an index into contiguous pieces of near equal size
+    fn split(&self, index: usize, pieces: usize) -> Vec<Combinations<T>> {
+        let choices = &self.elements[index];
+        let mut split = vec![];
+        for piece in 0..pieces {
+            let start = piece * choices.len() / pieces;
+            let end = (piece + 1) * choices.len() / pieces;
+            let mut elements = self.elements.clone();
+            elements[index] = choices[start..end].to_vec();
+            split.push(Self::new_shard(
+                elements,
+                self.permutations.clone(),
+                self.permute_indices.clone(),
+                self.length,
+                self.permutation.clone(),
+            ));
+        }
+        split
+    }
+}
+
+/// Owned iterator over the combinations so standard adapters like `take` and `skip` can be used
+pub struct IntoIter<T>(Combinations<T>);
+
+impl<T: Clone + Debug> Iterator for IntoIter<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().cloned()
+    }
+}
+
+impl<T: Clone + Debug> IntoIterator for Combinations<T> {
+    type Item = Vec<T>;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
     }
 }
 
 impl Combinations<String> {
-    /// Write all combinations to a gz in parallel (very fast with multiple CPUs)
-    pub async fn write_zip(&mut self, filename: &str, log: &Logger) -> Result<()> {
-        let err = format_err!("Failed to create gzip file '{:?}'", filename);
-        let file = File::create(filename).map_err(|_| err)?;
-        let writer = BufWriter::new(file);
+    /// Write all combinations to gz files in parallel (very fast with multiple CPUs)
+    /// Splits into numbered shards of at most `limit` lines, returning the filenames
+    pub async fn write_zip(
+        &mut self,
+        filename: &str,
+        limit: u64,
+        level: u32,
+        log: &Logger,
+    ) -> Result<Vec<String>> {
+        let shards = max(self.total().div_ceil(limit), 1);
+        let mut filenames = vec![filename.to_string()];
+        if shards > 1 {
+            filenames = (1..=shards)
+                .map(|i| filename.replace(".gz", &format!("{}.gz", i)))
+                .collect();
+        }
         let logname = format!("Writing Dictionary '{}'", filename);
         let timer = log.time(&logname, self.total()).await;
         let timer_handle = timer.start().await;
 
-        let mut parz: ParCompress<Gzip> = ParCompressBuilder::new().from_writer(writer);
         let mut as_bytes = self.to_bytes();
-        while let Some(strs) = as_bytes.next() {
-            for str in strs {
-                parz.write_all(str).expect("Failed to write");
+        for filename in &filenames {
+            let err = format_err!("Failed to create gzip file '{:?}'", filename);
+            let file = File::create(filename).map_err(|_| err)?;
+            let writer = BufWriter::new(file);
+            let mut parz: ParCompress<Gzip> = ParCompressBuilder::new()
+                .compression_level(Compression::new(level))
+                .from_writer(writer);
+            let mut lines = 0;
+            while lines < limit {
+                match as_bytes.next() {
+                    None => break,
+                    Some(strs) => {
+                        for str in strs {
+                            parz.write_all(str).expect("Failed to write");
+                        }
+                    }
+                }
+                parz.write(&[10]).unwrap();
+                timer.add(1);
+                lines += 1;
             }
-            parz.write(&[10]).unwrap();
-            timer.add(1);
+            parz.finish().map_err(Error::msg)?;
         }
 
-        parz.finish().map_err(Error::msg)?;
         timer_handle.await.expect("Timer failed");
-        Ok(())
+        Ok(filenames)
     }
 
     fn to_bytes(&self) -> Combinations<&[u8]> {
@@ -343,6 +467,50 @@ mod tests {
         assert_eq!(combinations.end(), vec![4, 3, 2]);
     }
 
+    #[test]
+    fn estimates_reproducibly() {
+        let elements: Vec<Vec<u64>> = (1..=8).map(|i| (0..i).collect()).collect();
+        let mut combinations = Combinations::permute(elements, (0..8).collect(), 6);
+        let estimate = combinations.estimate_total(100);
+        assert_ne!(estimate, combinations.estimate_total(u64::MAX));
+
+        combinations.next();
+        assert_eq!(combinations.estimate_total(100), estimate);
+        assert_eq!(combinations.closest_first().estimate_total(100), estimate);
+        assert_eq!(combinations.clone().estimate_total(100), estimate);
+    }
+
+    #[test]
+    fn can_iterate_owned() {
+        let combinations =
+            Combinations::permute(vec![vec![1, 2], vec![3], vec![4, 5]], vec![0, 1, 2], 3);
+        let all = expand(vec![combinations.clone()]);
+        assert_eq!(combinations.clone().into_iter().collect::<Vec<_>>(), all);
+        let skipped: Vec<_> = combinations.into_iter().skip(2).take(3).collect();
+        assert_eq!(skipped, all[2..5]);
+    }
+
+    #[test]
+    fn can_slice() {
+        let combinations = Combinations::new(vec![vec![1, 2], vec![3, 4, 5], vec![6, 7]]);
+        let slices: Vec<_> = (0..3).map(|i| combinations.slice(3, i).unwrap()).collect();
+        assert!(slices.iter().all(|slice| slice.total() == 4));
+        let mut sliced = expand(slices);
+        sliced.sort();
+        assert_eq!(sliced, expand(vec![combinations.clone()]));
+        assert!(combinations.slice(4, 0).is_none());
+
+        let elements = vec![vec![1], vec![2], vec![3], vec![4, 5]];
+        let combinations = Combinations::permute(elements, vec![0, 1, 2, 3], 3);
+        let slices: Vec<_> = (0..5).map(|i| combinations.slice(5, i).unwrap()).collect();
+        let mut sharded = expand(slices[1].shard(3));
+        let mut slice = expand(vec![slices[1].clone()]);
+        sharded.sort();
+        slice.sort();
+        assert_eq!(sharded, slice);
+        assert_eq!(expand(slices), expand(vec![combinations]));
+    }
+
     #[test]
     fn can_shard() {
         let combinations = Combinations::new(vec![vec![1, 2], vec![3, 4], vec![5, 6], vec![7, 8]]);
@@ -379,6 +547,30 @@ mod tests {
         assert_eq!(expand(vec![combinations.clone()]), expand(shards));
     }
 
+    #[test]
+    fn balances_shards() {
+        let combinations = Combinations::new(vec![vec![1, 2], (0..100).collect(), vec![3]]);
+        let shards = combinations.shard(10);
+        assert_eq!(shards.len(), 10);
+        for shard in &shards {
+            assert_eq!(shard.total(), 20);
+        }
+        assert_eq!(
+            expand(vec![combinations.clone()]).len(),
+            expand(shards).len()
+        );
+
+        // Splits across multiple indices when one isn't enough
+        let combinations = Combinations::new(vec![vec![1, 2], vec![3, 4, 5], vec![6, 7]]);
+        let shards = combinations.shard(6);
+        assert_eq!(shards.len(), 6);
+        assert!(shards.iter().all(|shard| shard.total() == 2));
+
+        // Cannot split further than every combination
+        let combinations = Combinations::new(vec![vec![1, 2], vec![3]]);
+        assert_eq!(combinations.shard(100).len(), 2);
+    }
+
     #[test]
     fn writes_permutations1() {
         let mut combinations =
@@ -438,6 +630,22 @@ mod tests {
         assert_eq!(perms.len() as u64, exact);
     }
 
+    #[test]
+    fn writes_closest_permutations() {
+        let mut combinations = Combinations::permute(
+            vec![vec![1], vec![2], vec![3], vec![4]],
+            vec![0, 1, 2, 3],
+            4,
+        )
+        .closest_first();
+        assert_eq!(combinations.next(), Some(&vec![1, 2, 3, 4]));
+        assert_eq!(combinations.next(), Some(&vec![1, 2, 4, 3]));
+        assert_eq!(combinations.next(), Some(&vec![1, 3, 2, 4]));
+        assert_eq!(combinations.next(), Some(&vec![2, 1, 3, 4]));
+        assert_eq!(combinations.total(), 24);
+        assert_eq!(combinations.shard(10).len(), 1);
+    }
+
     #[test]
     fn writes_permutations2() {
         let mut combinations =
@@ -477,4 +685,21 @@ mod tests {
         assert_eq!(combinations.next(), Some(&vec![2, 3, 5]));
         assert_eq!(combinations.next(), None);
     }
+
+    #[tokio::test]
+    async fn writes_zip_shards() {
+        let words = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let mut combinations = Combinations::new(vec![words.clone(), words]);
+        let log = Logger::off();
+        let files = combinations.write_zip("hc_shard.gz", 4, 1, &log).await;
+        let files = files.unwrap();
+        assert_eq!(files, vec!["hc_shard1.gz", "hc_shard2.gz", "hc_shard3.gz"]);
+        for file in files {
+            std::fs::remove_file(file).unwrap();
+        }
+
+        let files = combinations.write_zip("hc_shard.gz", 9, 1, &log).await;
+        assert_eq!(files.unwrap(), vec!["hc_shard.gz"]);
+        std::fs::remove_file("hc_shard.gz").unwrap();
+    }
 }
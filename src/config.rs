@@ -0,0 +1,251 @@
+use std::fs;
+use std::io::Read;
+
+use anyhow::{bail, format_err, Result};
+use clap::CommandFactory;
+use toml::{Table, Value};
+
+use crate::Cli;
+
+const CONFIG_FLAG: &str = "--config";
+const ARGS_STDIN_FLAG: &str = "--args-stdin";
+const STDIN_SOURCE: &str = "stdin";
+const HASHCAT_KEY: &str = "hashcat";
+
+/// Inserts the options from a '--config' TOML file that are not already on the command line
+pub fn expand_config(args: Vec<String>) -> Result<Vec<String>> {
+    let split = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let (options, trailing) = args.split_at(split);
+    let path = match config_path(options) {
+        None => return Ok(args),
+        Some(path) => path,
+    };
+    let text =
+        fs::read_to_string(&path).map_err(|_| format_err!("Unable to read config '{}'", path))?;
+    let table = text
+        .parse::<Table>()
+        .map_err(|err| format_err!("Invalid TOML in config '{}'\n{}", path, err))?;
+    expand_table(options, trailing, table, &path)
+}
+
+/// Inserts the options from a JSON object read from stdin when passed '--args-stdin'
+pub fn expand_args_stdin(args: Vec<String>, mut stdin: impl Read) -> Result<Vec<String>> {
+    let split = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let (options, trailing) = args.split_at(split);
+    if !options.iter().any(|option| option == ARGS_STDIN_FLAG) {
+        return Ok(args);
+    }
+    let mut text = String::new();
+    stdin
+        .read_to_string(&mut text)
+        .map_err(|_| format_err!("Unable to read the options from stdin"))?;
+    let json = serde_json::from_str(&text)
+        .map_err(|err| format_err!("Invalid JSON options from stdin\n{}", err))?;
+    let Some(Value::Table(table)) = json_value(json) else {
+        bail!("Options from stdin must be a JSON object e.g. {{\"seed\": \"zoo ?\"}}");
+    };
+    expand_table(options, trailing, table, STDIN_SOURCE)
+}
+
+/// The expanded args without '--args-stdin' so they can be run again without reading stdin
+pub fn without_args_stdin(args: &[String]) -> Vec<String> {
+    let split = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    let (options, trailing) = args.split_at(split);
+    let options = options.iter().filter(|option| *option != ARGS_STDIN_FLAG);
+    options.chain(trailing).cloned().collect()
+}
+
+/// Converts JSON into the equivalent TOML so it is expanded the same way as a config, skipping nulls
+fn json_value(json: serde_json::Value) -> Option<Value> {
+    use serde_json::Value as Json;
+    Some(match json {
+        Json::Null => return None,
+        Json::Bool(bool) => Value::Boolean(bool),
+        Json::Number(num) => match num.as_i64() {
+            Some(int) => Value::Integer(int),
+            None => Value::Float(num.as_f64()?),
+        },
+        Json::String(str) => Value::String(str),
+        Json::Array(list) => Value::Array(list.into_iter().filter_map(json_value).collect()),
+        Json::Object(map) => Value::Table(
+            map.into_iter()
+                .filter_map(|(key, value)| Some((key, json_value(value)?)))
+                .collect(),
+        ),
+    })
+}
+
+fn expand_table(
+    options: &[String],
+    trailing: &[String],
+    table: Table,
+    path: &str,
+) -> Result<Vec<String>> {
+    let command = Cli::command();
+    let mut expanded = options.to_vec();
+    let mut hashcat = trailing.to_vec();
+    for (key, value) in table {
+        if key == HASHCAT_KEY {
+            if hashcat.is_empty() {
+                hashcat.push("--".to_string());
+                hashcat.extend(values(&key, &value)?);
+            }
+            continue;
+        }
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(key.as_str()))
+            .filter(|_| key != CONFIG_FLAG[2..] && key != ARGS_STDIN_FLAG[2..])
+            .ok_or_else(|| format_err!("Unknown option '{}' in config '{}'", key, path))?;
+        if is_present(options, &key, arg.get_short()) {
+            continue;
+        }
+        match value {
+            Value::Boolean(true) => expanded.push(format!("--{}", key)),
+            Value::Boolean(false) => {}
+            value => {
+                for value in values(&key, &value)? {
+                    expanded.push(format!("--{}", key));
+                    expanded.push(value);
+                }
+            }
+        }
+    }
+    expanded.extend(hashcat);
+    Ok(expanded)
+}
+
+fn config_path(options: &[String]) -> Option<String> {
+    let prefix = format!("{}=", CONFIG_FLAG);
+    let mut iter = options.iter();
+    while let Some(option) = iter.next() {
+        if option == CONFIG_FLAG {
+            return iter.next().cloned();
+        }
+        if let Some(path) = option.strip_prefix(&prefix) {
+            return Some(path.to_string());
+        }
+    }
+    None
+}
+
+fn is_present(options: &[String], long: &str, short: Option<char>) -> bool {
+    let long = format!("--{}", long);
+    let long_value = format!("{}=", long);
+    let short = short.map(|short| format!("-{}", short));
+    options.iter().any(|option| {
+        *option == long || option.starts_with(&long_value) || Some(option) == short.as_ref()
+    })
+}
+
+fn values(key: &str, value: &Value) -> Result<Vec<String>> {
+    match value {
+        Value::String(str) => Ok(vec![str.clone()]),
+        Value::Integer(num) => Ok(vec![num.to_string()]),
+        Value::Array(list) => {
+            let mut all = vec![];
+            for value in list {
+                match value {
+                    Value::Array(_) => bail!("Config option '{}' cannot contain nested lists", key),
+                    value => all.extend(values(key, value)?),
+                }
+            }
+            Ok(all)
+        }
+        _ => bail!("Config option '{}' must be a string, number or list", key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split(" ").map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn expands_config_files() {
+        let toml = r#"
+            address = "1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9"
+            seed = "zoo,?"
+            passphrase = ["?d", "./dicts/1k.txt"]
+            custom-charset1 = "abc"
+            combinations = 12
+            skip-prompt = true
+            xpub-preview = false
+            hashcat = ["-w", "3"]
+        "#;
+        fs::write("test_config.toml", toml).unwrap();
+
+        let expanded = expand_config(args("seedcat --config test_config.toml -s zoo")).unwrap();
+        assert_eq!(
+            expanded.join(" "),
+            "seedcat --config test_config.toml -s zoo \
+             --address 1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9 --combinations 12 \
+             --custom-charset1 abc --passphrase ?d --passphrase ./dicts/1k.txt \
+             --skip-prompt -- -w 3"
+        );
+
+        let expanded = expand_config(args("seedcat --config=test_config.toml -- -O")).unwrap();
+        assert!(expanded.contains(&"--seed".to_string()));
+        assert!(expanded.ends_with(&args("-- -O")));
+
+        fs::write("test_config.toml", "unknown = 1").unwrap();
+        assert!(expand_config(args("seedcat --config test_config.toml")).is_err());
+        fs::write("test_config.toml", "seed = {}").unwrap();
+        assert!(expand_config(args("seedcat --config test_config.toml")).is_err());
+        fs::remove_file("test_config.toml").unwrap();
+
+        assert!(expand_config(args("seedcat --config missing.toml")).is_err());
+        assert_eq!(
+            expand_config(args("seedcat -s zoo")).unwrap(),
+            args("seedcat -s zoo")
+        );
+    }
+
+    #[test]
+    fn expands_json_from_stdin() {
+        let json = r#"{
+            "address": "1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9",
+            "seed": "zoo ? \"quoted\"",
+            "passphrase": ["?d", "it's"],
+            "combinations": 12,
+            "skip-prompt": true,
+            "xpub-preview": false,
+            "derivation": null,
+            "hashcat": ["-w", "3"]
+        }"#;
+        let expanded = expand_args_stdin(args("seedcat --args-stdin -s zoo"), json.as_bytes());
+        let mut expected = args("seedcat --args-stdin -s zoo");
+        expected.extend(args(
+            "--address 1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9 --combinations 12 --passphrase ?d",
+        ));
+        expected.extend(["--passphrase".to_string(), "it's".to_string()]);
+        expected.extend(args("--skip-prompt -- -w 3"));
+        assert_eq!(expanded.unwrap(), expected);
+
+        let unread = "not json".as_bytes();
+        let expanded = expand_args_stdin(args("seedcat -s zoo"), unread).unwrap();
+        assert_eq!(expanded, args("seedcat -s zoo"));
+
+        let stdin = args("seedcat --args-stdin");
+        assert!(expand_args_stdin(stdin.clone(), "not json".as_bytes()).is_err());
+        assert!(expand_args_stdin(stdin.clone(), "[\"-s\"]".as_bytes()).is_err());
+        assert!(expand_args_stdin(stdin.clone(), r#"{"args-stdin": true}"#.as_bytes()).is_err());
+        assert!(expand_args_stdin(stdin, r#"{"unknown": 1}"#.as_bytes()).is_err());
+
+        let expanded = args("seedcat --args-stdin -s zoo -- --args-stdin");
+        let resumed = without_args_stdin(&expanded);
+        assert_eq!(resumed, args("seedcat -s zoo -- --args-stdin"));
+    }
+}
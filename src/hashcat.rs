@@ -1,23 +1,37 @@
+use std::cmp::{max, min};
+use std::collections::VecDeque;
 use std::env;
+use std::fs;
 use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Child, ChildStderr, ChildStdin, Command, Stdio};
+use std::process;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use anyhow::{format_err, Error, Result};
+use anyhow::{bail, format_err, Error, Result};
 use crossterm::style::Stylize;
 use gzp::deflate::Gzip;
 use gzp::par::compress::{ParCompress, ParCompressBuilder};
-use gzp::ZWriter;
+use gzp::{Compression, ZWriter};
+use serde_json::json;
+use tokio::signal;
 use tokio::spawn;
 use tokio::sync::mpsc::channel;
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::mpsc::Sender;
+use tokio::task::spawn_blocking;
+use tokio::time::sleep;
 
 use crate::address::AddressValid;
+use crate::backend::{kill_running, HashcatBackend, HashcatProcess, ProcessBackend, Running};
 use crate::logger::{Attempt, Logger, Timer};
 use crate::passphrase::Passphrase;
 use crate::seed::{Finished, Seed};
+use crate::{DEFAULT_COMPRESSION, DEFAULT_HASH_MODE};
 
 const DEFAULT_MAX_HASHES: u64 = 10_000_000;
 const DEFAULT_MIN_PASSPHRASES: u64 = 10_000;
@@ -25,11 +39,18 @@ const HC_PID_FILE: &str = "hashcat.pid";
 const HC_HASHES_FILE: &str = "_hashes.gz";
 const HC_ERROR_FILE: &str = "_error.log";
 const HC_OUTPUT_FILE: &str = "_output.log";
+const HC_METRICS_FILE: &str = "_metrics.json";
+const HC_PASSPHRASES_FILE: &str = "_passphrases.txt";
+const HC_CANDIDATES_FILE: &str = "_candidates";
+const CANDIDATE_FILE_LINES: u64 = 1_000_000_000;
 const CHANNEL_SIZE: usize = 100;
 const SEED_TASKS: usize = 1000;
+const ORDERED_TASKS: usize = 32;
 const STDIN_PASSPHRASE_MEM: usize = 10_000_000;
 const STDIN_BUFFER_BYTES: usize = 1000;
 const S_MODE_MAXIMUM: u64 = 100_000_000;
+const INTERRUPT_GRACE_SECS: u64 = 2;
+const PROGRESS_MISMATCH_PERCENT: u64 = 10;
 
 /// Wrapper for the location of the exe
 #[derive(Debug, Clone)]
@@ -42,23 +63,152 @@ impl HashcatExe {
         Self { exe }
     }
 
+    /// Path to the hashcat executable
+    pub fn path(&self) -> &Path {
+        &self.exe
+    }
+
+    /// Folder containing hashcat where it runs and temp files are written
+    pub fn hashcat_dir(&self) -> &Path {
+        self.exe.parent().expect("parent folder exists")
+    }
+
+    /// Folder containing seedcat where dicts are found
+    pub fn seedcat_dir(&self) -> &Path {
+        self.hashcat_dir().parent().expect("parent folder exists")
+    }
+
     /// Move to seedcat folder for finding dicts
     fn cd_seedcat(&self) {
-        let parent = self.exe.parent().expect("parent folder exists");
-        let parent = parent.parent().expect("parent folder exists");
-        env::set_current_dir(&parent).expect("can set dir");
+        env::set_current_dir(self.seedcat_dir()).expect("can set dir");
     }
 
     /// Move to hashcat folder for running
     fn cd_hashcat(&self) {
-        let parent = self.exe.parent().expect("parent folder exists");
-        env::set_current_dir(&parent).expect("can set dir");
+        env::set_current_dir(self.hashcat_dir()).expect("can set dir");
     }
 
     /// Create command from the exe path
     fn command(&self) -> Command {
         Command::new(self.exe.clone())
     }
+
+    /// Probes hashcat for the backend devices it detects
+    pub fn devices(&self) -> Result<Vec<HashcatDevice>> {
+        let mut cmd = self.command();
+        cmd.arg("-I").current_dir(self.hashcat_dir());
+        let output = cmd
+            .output()
+            .map_err(|err| format_err!("Could not start hashcat process: {}\n{:?}", err, cmd))?;
+        Ok(Self::parse_devices(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Prints every passphrase candidate using hashcat's --stdout mode
+    pub async fn print_passphrases(&self, passphrase: &Passphrase, log: &Logger) -> Result<()> {
+        self.cd_hashcat();
+        let result = self.run_stdout_passphrases(passphrase, log).await;
+        self.cd_seedcat();
+        result
+    }
+
+    async fn run_stdout_passphrases(&self, passphrase: &Passphrase, log: &Logger) -> Result<()> {
+        let mut cmd = self.command();
+        cmd.arg("--stdout");
+        cmd.arg("--session");
+        cmd.arg(format!("hc{}stdout", process::id()));
+        cmd.args(passphrase.build_args("hc", log).await?);
+        log.println_debug(&format!("Running {:?}", cmd));
+        let status = cmd
+            .status()
+            .map_err(|err| format_err!("Could not start hashcat process: {}\n{:?}", err, cmd))?;
+        if !status.success() {
+            bail!("Hashcat failed to generate passphrases ({})", status);
+        }
+        Ok(())
+    }
+
+    /// Runs the hashcat benchmark returning the BIP39 hashes per second
+    pub fn benchmark(&self) -> Result<u64> {
+        let mut cmd = self.command();
+        cmd.args(["-b", "-m", &DEFAULT_HASH_MODE.to_string()])
+            .current_dir(self.hashcat_dir());
+        let output = cmd
+            .output()
+            .map_err(|err| format_err!("Could not start hashcat process: {}\n{:?}", err, cmd))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Self::parse_speed(&stdout)
+            .ok_or_else(|| format_err!("Unable to parse the hashcat benchmark speed\n{}", stdout))
+    }
+
+    /// Parses 'Speed.#*.........: <num> <unit>' or sums the 'Speed.#<id>' of each device
+    fn parse_speed(output: &str) -> Option<u64> {
+        let mut devices = vec![];
+        for line in output.lines().map(|line| line.trim()) {
+            let Some(speed) = line.strip_prefix("Speed.#") else {
+                continue;
+            };
+            let mut split = speed.split(": ").nth(1)?.split_whitespace();
+            let num = split.next()?.parse::<f64>().ok()?;
+            let multiplier = match split.next()? {
+                "H/s" => 1.0,
+                "kH/s" => 1e3,
+                "MH/s" => 1e6,
+                "GH/s" => 1e9,
+                "TH/s" => 1e12,
+                _ => return None,
+            };
+            let hashes = (num * multiplier) as u64;
+            if speed.starts_with('*') {
+                return Some(hashes);
+            }
+            devices.push(hashes);
+        }
+        match devices.is_empty() {
+            true => None,
+            false => Some(devices.iter().sum()),
+        }
+    }
+
+    /// Parses the devices from `hashcat -I` skipping aliases of the same device
+    fn parse_devices(output: &str) -> Vec<HashcatDevice> {
+        let mut devices: Vec<HashcatDevice> = vec![];
+        let mut aliases = vec![];
+        let mut is_alias = false;
+        for line in output.lines().map(|line| line.trim()) {
+            if let Some(header) = line.strip_prefix("Backend Device ID #") {
+                let mut split = header.split(" (Alias: #");
+                let id = split.next().unwrap_or_default().to_string();
+                is_alias = aliases.contains(&id);
+                if let Some(alias) = split.next() {
+                    aliases.push(alias.trim_end_matches(')').to_string());
+                }
+                if !is_alias {
+                    devices.push(HashcatDevice {
+                        id,
+                        name: String::new(),
+                        is_cpu: false,
+                    });
+                }
+            } else if let Some(device) = devices.last_mut().filter(|_| !is_alias) {
+                if let Some(name) = line.strip_prefix("Name...........: ") {
+                    device.name = name.to_string();
+                } else if let Some(kind) = line.strip_prefix("Type...........: ") {
+                    device.is_cpu = kind == "CPU";
+                }
+            }
+        }
+        devices
+    }
+}
+
+/// A device hashcat can run on
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashcatDevice {
+    pub id: String,
+    pub name: String,
+    pub is_cpu: bool,
 }
 
 /// Information about the hashcat mode
@@ -90,6 +240,16 @@ impl HashcatMode {
     }
 }
 
+/// How the hashcat process finished
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum HashcatExit {
+    // Every address and match hashcat reported, more than one when finding all
+    Found(Vec<(String, String)>),
+    Exhausted,
+    // Finished without covering every guess
+    Stopped,
+}
+
 /// Represents how hashcat will be run
 #[derive(Debug, Clone)]
 pub enum HashcatRunner {
@@ -103,44 +263,168 @@ pub enum HashcatRunner {
     StdinMinPassphrases,
 }
 
+impl HashcatRunner {
+    /// Short name used in the metrics file
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashcatRunner::PureGpu => "pure_gpu",
+            HashcatRunner::BinaryCharsets(_, _) => "binary_charsets",
+            HashcatRunner::StdinMaxHashes => "stdin_max_hashes",
+            HashcatRunner::StdinMinPassphrases => "stdin_min_passphrases",
+        }
+    }
+}
+
 /// Helper for running hashcat
 pub struct Hashcat {
-    address: AddressValid,
+    addresses: Vec<AddressValid>,
     seed: Seed,
     passphrase: Option<Passphrase>,
     pub max_hashes: u64,
     pub min_passphrases: u64,
     exe: HashcatExe,
     prefix: String,
+    session: Option<String>,
     hashcat_args: Vec<String>,
     total: u64,
+    backend: Arc<dyn HashcatBackend>,
+    compression: u32,
+    hash_mode: u32,
+    then_passphrases: Vec<Passphrase>,
+    // Some(is_pure_gpu) if the hashes file can be reused by the next passphrase attack
+    written_hashes: Option<bool>,
+    ordered: bool,
+    // Overrides whether hashcat runs with -S, otherwise chosen by the passphrases
+    force_s_mode: Option<bool>,
+    // Writes the stdin mode candidates to files instead of piping them
+    candidate_files: bool,
+    // Keeps hashcat running after the first match to report every match
+    find_all: bool,
+    resume: Resume,
+}
+
+/// Tracks how far hashcat got so a stopped run can print the command that resumes it
+#[derive(Debug, Clone, Default)]
+struct Resume {
+    // The original command line, None if a single '--skip' cannot resume the run
+    args: Option<Vec<String>>,
+    // The '--skip' position from the last hashcat status
+    restore_point: Arc<AtomicU64>,
+    // Stdin mode candidates only reach hashcat in the same order every run when ordered
+    resumable: Arc<AtomicBool>,
+}
+
+impl Resume {
+    fn command(&self) -> Option<String> {
+        let args = self.args.as_ref()?;
+        let skip = self.restore_point.load(Ordering::Relaxed);
+        if skip == 0 || !self.resumable.load(Ordering::Relaxed) {
+            return None;
+        }
+        let args: Vec<_> = Self::with_skip(args, skip)
+            .iter()
+            .map(|arg| Self::quote(arg))
+            .collect();
+        Some(args.join(" "))
+    }
+
+    /// Replaces any hashcat '--skip' or '--restore' passed after '--' with the new position
+    fn with_skip(args: &[String], skip: u64) -> Vec<String> {
+        let split = args.iter().position(|arg| arg == "--");
+        let (seedcat, hashcat) = args.split_at(split.unwrap_or(args.len()));
+        let mut resume = seedcat.to_vec();
+        resume.push("--".to_string());
+        let mut hashcat = hashcat.iter().skip(1);
+        while let Some(arg) = hashcat.next() {
+            if arg == "-s" || arg == "--skip" {
+                hashcat.next();
+            } else if !arg.starts_with("--skip=") && arg != "--restore" {
+                resume.push(arg.clone());
+            }
+        }
+        resume.push("--skip".to_string());
+        resume.push(skip.to_string());
+        resume
+    }
+
+    /// Quotes an arg so it can be pasted into a shell
+    fn quote(arg: &str) -> String {
+        let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c);
+        if !arg.is_empty() && arg.chars().all(safe) {
+            return arg.to_string();
+        }
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
 }
 
 impl Hashcat {
     pub fn new(
         exe: HashcatExe,
-        address: AddressValid,
+        addresses: Vec<AddressValid>,
         seed: Seed,
         passphrase: Option<Passphrase>,
         hashcat_args: Vec<String>,
     ) -> Self {
-        let mut total = seed.total();
-        total = total.saturating_mul(address.derivations.total());
-        if let Some(passphrase) = &passphrase {
-            total = total.saturating_mul(passphrase.total());
-        }
+        let total = Self::guesses(&seed, &addresses, &passphrase);
 
         Self {
             exe,
-            address,
+            addresses,
             seed,
             passphrase,
             max_hashes: DEFAULT_MAX_HASHES,
             prefix: "hc".to_string(),
+            session: None,
             min_passphrases: DEFAULT_MIN_PASSPHRASES,
             hashcat_args,
             total,
+            backend: Arc::new(ProcessBackend),
+            compression: DEFAULT_COMPRESSION,
+            hash_mode: DEFAULT_HASH_MODE,
+            then_passphrases: vec![],
+            written_hashes: None,
+            ordered: false,
+            force_s_mode: None,
+            candidate_files: false,
+            find_all: false,
+            resume: Resume::default(),
+        }
+    }
+
+    fn guesses(seed: &Seed, addresses: &[AddressValid], passphrase: &Option<Passphrase>) -> u64 {
+        let derivations = addresses.iter().map(|a| a.derivations.total()).sum();
+        let mut total = seed.total();
+        total = total.saturating_mul(derivations);
+        if let Some(passphrase) = &passphrase {
+            total = total.saturating_mul(passphrase.total());
+        }
+        total
+    }
+
+    /// How the seeds, derivations and passphrases multiply into the total guesses
+    pub fn total_breakdown(&self) -> String {
+        let derivations = self.addresses.iter().map(|a| a.derivations.total()).sum();
+        let mut factors = vec![
+            format!("{} seeds", Logger::format_num(self.seed.total())),
+            format!("{} derivations", Logger::format_num(derivations)),
+        ];
+        if let Some(passphrase) = &self.passphrase {
+            let passphrases = self
+                .then_passphrases
+                .iter()
+                .fold(passphrase.total(), |sum, p| sum.saturating_add(p.total()));
+            factors.push(format!("{} passphrases", Logger::format_num(passphrases)));
+        }
+        factors.join(" x ")
+    }
+
+    /// Passphrase attacks to try in order if the previous attack is exhausted
+    pub fn set_then_passphrases(&mut self, passphrases: Vec<Passphrase>) {
+        for passphrase in &passphrases {
+            let guesses = Self::guesses(&self.seed, &self.addresses, &Some(passphrase.clone()));
+            self.total = self.total.saturating_add(guesses);
         }
+        self.then_passphrases = passphrases;
     }
 
     /// Total guesses we will make
@@ -153,9 +437,186 @@ impl Hashcat {
         self.prefix = prefix;
     }
 
+    /// Replaces how the hashcat process is spawned
+    #[cfg(test)]
+    pub fn set_backend(&mut self, backend: Arc<dyn HashcatBackend>) {
+        self.backend = backend;
+    }
+
+    /// Always runs in pure GPU mode when the seed is known and only passphrases are guessed
+    pub fn force_pure_gpu(&mut self) {
+        self.min_passphrases = 0;
+    }
+
+    /// The addresses being recovered, only one unless the address type is unknown
+    pub fn addresses(&self) -> &Vec<AddressValid> {
+        &self.addresses
+    }
+
+    /// The address that matched the found seed
+    pub fn found_address(&self, finished: &Finished) -> &AddressValid {
+        self.addresses
+            .iter()
+            .find(|address| finished.address.as_ref() == Some(&address.formatted))
+            .unwrap_or(&self.addresses[0])
+    }
+
+    /// Number of hash lines written per seed
+    fn derivation_args(&self) -> u64 {
+        let args = self.addresses.iter().map(|a| a.derivations.args().len());
+        args.sum::<usize>() as u64
+    }
+
+    /// How many derivations hashcat performs per hash line
+    fn derivation_ratio(&self) -> f64 {
+        let total: u64 = self.addresses.iter().map(|a| a.derivations.total()).sum();
+        total as f64 / self.derivation_args() as f64
+    }
+
+    /// The hashcat executable that will be run
+    pub fn exe(&self) -> &HashcatExe {
+        &self.exe
+    }
+
+    /// Kills hashcat and removes the temp files (unless `keep_files`) if seedcat is interrupted
+    pub fn spawn_interrupt_handler(&self, keep_files: bool, log: &Logger) {
+        let dir = self.exe.hashcat_dir().to_path_buf();
+        let prefix = format!("{}_", self.prefix);
+        let log = log.clone();
+        let resume = self.resume.clone();
+        spawn(async move {
+            if signal::ctrl_c().await.is_err() {
+                return;
+            }
+            log.println_warn("\nInterrupted, stopping hashcat...");
+            // hashcat also receives the interrupt from the terminal and writes its restore file
+            sleep(Duration::from_secs(INTERRUPT_GRACE_SECS)).await;
+            kill_running();
+            if !keep_files {
+                Self::remove_temp_files(&dir, &prefix);
+            }
+            Self::log_resume(&log, resume.command());
+            process::exit(130);
+        });
+    }
+
+    /// Sets the original command line so a stopped run can print how to resume it
+    pub fn set_resume_args(&mut self, args: Vec<String>) {
+        self.resume.args = Some(args);
+    }
+
+    /// The command that resumes from where hashcat stopped, if it is known
+    pub fn resume_command(&self) -> Option<String> {
+        self.resume.command()
+    }
+
+    /// Prints the command that resumes the run, if it is known
+    pub fn log_resume(log: &Logger, command: Option<String>) {
+        if let Some(command) = command {
+            log.println("\nTo resume from where hashcat stopped run:".bold());
+            log.println(command.as_str().stylize());
+        }
+    }
+
+    fn remove_temp_files(dir: &Path, prefix: &str) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(prefix) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    /// Pattern matching the temp files written while running
+    pub fn temp_files(&self) -> PathBuf {
+        self.exe.hashcat_dir().join(format!("{}_*", self.prefix))
+    }
+
+    /// Sets the gzip level of the temp files, lower levels start hashcat faster
+    pub fn set_compression(&mut self, compression: u32) {
+        self.compression = compression;
+        self.passphrase = self
+            .passphrase
+            .as_ref()
+            .map(|p| p.with_compression(compression));
+        for passphrase in &mut self.then_passphrases {
+            *passphrase = passphrase.with_compression(compression);
+        }
+    }
+
+    /// Sends seeds to hashcat in the same order every run so skipping and restoring resumes correctly
+    pub fn set_ordered(&mut self, ordered: bool) {
+        self.ordered = ordered;
+    }
+
+    /// Sets the hashcat '-m' module for forks that number the BIP39 module differently
+    pub fn set_hash_mode(&mut self, hash_mode: u32) {
+        self.hash_mode = hash_mode;
+    }
+
+    /// Forces hashcat's -S mode on or off instead of choosing it by the passphrases
+    pub fn set_s_mode(&mut self, s_mode: Option<bool>) {
+        self.force_s_mode = s_mode;
+    }
+
+    /// In stdin mode writes every candidate to files hashcat reads as dictionaries
+    pub fn set_candidate_files(&mut self, candidate_files: bool) {
+        self.candidate_files = candidate_files;
+    }
+
+    /// Keeps searching after a match so every matching hash line is reported
+    pub fn set_find_all(&mut self, find_all: bool) {
+        self.find_all = find_all;
+    }
+
+    /// Whether hashcat runs with -S (slow candidates) along with the reason why
+    pub fn s_mode(&self, mode: &HashcatMode) -> (bool, &'static str) {
+        let attack_mode = self
+            .passphrase
+            .as_ref()
+            .map(|p| p.attack_mode)
+            .unwrap_or_default();
+        match self.force_s_mode {
+            Some(true) => (true, "forced by --single-mode"),
+            Some(false) => (false, "disabled by --no-single-mode"),
+            None if !mode.is_pure_gpu() && self.candidate_files => {
+                (false, "candidates are passed via files")
+            }
+            None if !mode.is_pure_gpu() => (false, "candidates are passed via stdin"),
+            // -S mode is faster if we have <100M passphrases
+            None if mode.passphrases >= S_MODE_MAXIMUM => (false, "over 100M passphrases"),
+            None if attack_mode == 6 || attack_mode == 7 => (false, "hybrid passphrase attack"),
+            None => (true, "under 100M passphrases"),
+        }
+    }
+
+    /// Sets the hashcat session name used for restore files
+    pub fn set_session(&mut self, session: String) {
+        self.session = Some(session);
+    }
+
+    /// The hashcat session name, unique to this process by default
+    pub fn session(&self) -> String {
+        match &self.session {
+            None => format!("{}{}", self.prefix, process::id()),
+            Some(session) => session.clone(),
+        }
+    }
+
+    /// Explains why binary charsets weren't used if the custom charsets took their slots
+    pub fn binary_slots_warning(&self) -> Option<String> {
+        let warning = self.passphrase.as_ref()?.binary_slots_warning()?;
+        match self.seed.supports_binary_charsets(self.max_hashes) {
+            true => Some(warning),
+            false => None,
+        }
+    }
+
     /// Get the mode we will run in
     pub fn get_mode(&self) -> Result<HashcatMode> {
-        let total_derivations = self.address.derivations.args().len() as u64;
+        let total_derivations = self.derivation_args();
         let binary_charsets = self.seed.binary_charsets(self.max_hashes, &self.passphrase);
         if let Some((seed, passphrase)) = binary_charsets? {
             if passphrase.total() > self.min_passphrases {
@@ -166,19 +627,32 @@ impl Hashcat {
                 ));
             }
         }
-        let derivations = self.address.derivations.args().len() as u64;
+        let derivations = self.derivation_args();
         let passphrases = match &self.passphrase {
             None => 0,
             Some(passphrase) => passphrase.total(),
         };
 
+        let newlines = match &self.passphrase {
+            None => false,
+            Some(passphrase) => passphrase.has_newline_bytes()?,
+        };
+
         let gpu_hashes = self.seed.valid_seeds() * derivations;
         let stdin_hashes = self.seed.total_args() * derivations;
         if gpu_hashes > self.max_hashes {
+            if newlines {
+                bail!(
+                    "Passphrases containing the bytes '0a' or '0d' cannot be sent to hashcat over stdin\n\
+                     Guess fewer seeds (at most {} hashes) so the passphrases can run on the GPU",
+                    self.max_hashes
+                );
+            }
             let mode = HashcatMode::new(HashcatRunner::StdinMaxHashes, 0, stdin_hashes);
             return Ok(mode);
         }
-        if passphrases < self.min_passphrases {
+        // newlines would split the stdin candidates so these always run on the GPU
+        if passphrases < self.min_passphrases && !newlines {
             let mode = HashcatMode::new(HashcatRunner::StdinMinPassphrases, 0, stdin_hashes);
             return Ok(mode);
         }
@@ -186,9 +660,25 @@ impl Hashcat {
         Ok(mode)
     }
 
-    /// Runs the hashcat program
+    /// Runs the hashcat program, trying each passphrase attack until one succeeds
     pub async fn run(&mut self, log: &Logger, is_bench: bool) -> Result<(Timer, Finished)> {
+        let seed = self.seed.clone();
+        let mut result = self.run_attack(log, is_bench).await?;
+        for passphrase in self.then_passphrases.clone() {
+            if result.1.seed.is_some() {
+                break;
+            }
+            log.println("Passphrase attack exhausted, trying the next attack...".bold());
+            self.seed = seed.clone();
+            self.passphrase = Some(passphrase);
+            result = self.run_attack(log, is_bench).await?;
+        }
+        Ok(result)
+    }
+
+    async fn run_attack(&mut self, log: &Logger, is_bench: bool) -> Result<(Timer, Finished)> {
         self.exe.cd_hashcat();
+        let total = Self::guesses(&self.seed, &self.addresses, &self.passphrase);
 
         // Required on windows for stdin mode
         File::create(HC_PID_FILE).expect("can create pid file");
@@ -203,6 +693,10 @@ impl Hashcat {
 
         let mode = self.get_mode()?;
         let is_pure_gpu = mode.is_pure_gpu();
+        let runner = mode.runner.name();
+        let resumable = is_pure_gpu || self.ordered;
+        self.resume.resumable.store(resumable, Ordering::Relaxed);
+        self.resume.restore_point.store(0, Ordering::Relaxed);
 
         match mode.clone().runner {
             // All args get passed to hashcat, hashfile filled with valid seeds
@@ -211,11 +705,14 @@ impl Hashcat {
                     args.push(arg.clone());
                 }
                 self.seed = self.seed.with_pure_gpu(is_pure_gpu);
-                let seed_rx = self.spawn_seed_senders().await;
-                self.write_hashes(log, seed_rx, mode.hashes).await?;
+                if self.written_hashes != Some(true) {
+                    let seed_rx = self.spawn_seed_senders().await;
+                    self.write_hashes(log, seed_rx, mode.hashes).await?;
+                    self.written_hashes = Some(true);
+                }
 
-                let child = self.spawn_hashcat(&args, mode);
-                self.run_helper(child, log, is_bench).await
+                let child = self.spawn_hashcat(&args, mode, log)?;
+                self.run_helper(child, log, total, runner, is_bench).await
             }
             // All args get passed to hashcat, hashfile filled with args
             HashcatRunner::BinaryCharsets(seed, passphrase) => {
@@ -226,47 +723,276 @@ impl Hashcat {
                 self.seed = seed.with_pure_gpu(is_pure_gpu);
                 let rx = Self::spawn_arg_sender(&self.seed).await;
                 self.write_hashes(log, rx, mode.hashes).await?;
+                self.written_hashes = None;
 
-                let child = self.spawn_hashcat(&args, mode);
-                self.run_helper(child, log, is_bench).await
+                let child = self.spawn_hashcat(&args, mode, log)?;
+                self.run_helper(child, log, total, runner, is_bench).await
             }
             // Valid seeds and passphrases passed via stdin
             HashcatRunner::StdinMaxHashes | HashcatRunner::StdinMinPassphrases => {
                 self.seed = self.seed.with_pure_gpu(is_pure_gpu);
                 let seed_rx = self.spawn_seed_senders().await;
-                let rx = Self::spawn_arg_sender(&self.seed).await;
-                self.write_hashes(log, rx, mode.hashes).await?;
+                if self.written_hashes != Some(false) {
+                    let rx = Self::spawn_arg_sender(&self.seed).await;
+                    self.write_hashes(log, rx, mode.hashes).await?;
+                    self.written_hashes = Some(false);
+                }
 
-                let mut child = self.spawn_hashcat(&args, mode);
-                let stdin = child.stdin.take();
-                let stdin = HashcatStdin::new(stdin, passphrase_args, &self.exe);
-                spawn(Self::stdin_sender(self.prefix.clone(), stdin, seed_rx));
+                if self.candidate_files {
+                    let files = self
+                        .write_candidate_files(seed_rx, passphrase_args, log)
+                        .await?;
+                    args.extend(files.iter().map(|file| file.display().to_string()));
+                    let mut child = self.spawn_hashcat(&args, mode, log)?;
+                    // closed in case there were no candidates to write
+                    drop(child.take_stdin());
+                    let result = self.run_helper(child, log, total, runner, is_bench).await;
+                    for file in files {
+                        let _ = fs::remove_file(file);
+                    }
+                    return result;
+                }
+
+                let mut child = self.spawn_hashcat(&args, mode, log)?;
+                let stdin = child.take_stdin();
+                let tail = self.passphrases_file();
+                let stdin = HashcatStdin::new(stdin, passphrase_args, &self.exe, tail);
+                let sender = spawn(Self::stdin_sender(self.session(), stdin, seed_rx));
+
+                let result = self.run_helper(child, log, total, runner, is_bench).await;
+                // hashcat exits early if the sender fails so report the cause
+                if sender.is_finished() {
+                    sender.await.map_err(Error::msg)??;
+                } else {
+                    // dropping the sender removes the cached passphrases
+                    sender.abort();
+                }
+                result
+            }
+        }
+    }
 
-                self.run_helper(child, log, is_bench).await
+    /// The first seed and passphrase candidates, with the passphrase changing fastest
+    pub fn preview(&self, num: usize) -> Result<Vec<String>> {
+        let passphrases = match &self.passphrase {
+            Some(passphrase) => passphrase.preview(num)?,
+            None => vec![String::new()],
+        };
+        let mut result = vec![];
+        for seed in self.seed.preview(num) {
+            for passphrase in &passphrases {
+                if result.len() == num {
+                    return Ok(result);
+                }
+                result.push(match passphrase.is_empty() {
+                    true => seed.clone(),
+                    false => format!("{} {}", seed, passphrase),
+                });
             }
         }
+        Ok(result)
+    }
+
+    /// Writes the candidates hashcat would receive over stdin instead of running hashcat
+    pub async fn write_candidates(
+        &mut self,
+        out: Box<dyn Write + Send + Sync>,
+        log: &Logger,
+    ) -> Result<()> {
+        self.exe.cd_hashcat();
+        let mut passphrase_args = vec![];
+        if let Some(passphrase) = &self.passphrase {
+            passphrase_args = passphrase.build_args(&self.prefix, log).await?;
+        }
+        let result = self.send_candidates(out, passphrase_args, log).await;
+        self.exe.cd_seedcat();
+        result
+    }
+
+    async fn send_candidates(
+        &mut self,
+        out: Box<dyn Write + Send + Sync>,
+        passphrase_args: Vec<String>,
+        log: &Logger,
+    ) -> Result<()> {
+        // hashfile contains the fixed words needed to decode the candidates
+        self.seed = self.seed.with_pure_gpu(false);
+        let hashes = self.seed.total_args() * self.derivation_args();
+        let rx = Self::spawn_arg_sender(&self.seed).await;
+        self.write_hashes(log, rx, hashes).await?;
+
+        let seed_rx = self.spawn_seed_senders().await;
+        let tail = self.passphrases_file();
+        let stdin = HashcatStdin::new(Some(out), passphrase_args, &self.exe, tail);
+        Self::stdin_sender(self.session(), stdin, seed_rx).await
+    }
+
+    /// Writes every candidate before running hashcat so a stalled pipe cannot lose any
+    async fn write_candidate_files(
+        &self,
+        seed_rx: Receiver<Vec<u8>>,
+        passphrase_args: Vec<String>,
+        log: &Logger,
+    ) -> Result<Vec<PathBuf>> {
+        let passphrases = self.passphrase.as_ref().map_or(1, |p| p.total());
+        let candidates = self.seed.valid_seeds().saturating_mul(passphrases);
+        let timer = log.time("Writing Candidates", candidates).await;
+        let timer_handle = timer.start().await;
+
+        let name = self.prefix.clone() + HC_CANDIDATES_FILE;
+        let path = env::current_dir().unwrap_or_default().join(name);
+        let files = CandidateFiles::new(path, CANDIDATE_FILE_LINES, self.compression, &timer);
+        let filenames = files.filenames.clone();
+        let out: Box<dyn Write + Send + Sync> = Box::new(files);
+        let tail = self.passphrases_file();
+        let stdin = HashcatStdin::new(Some(out), passphrase_args, &self.exe, tail).with_files();
+        Self::stdin_sender(self.session(), stdin, seed_rx).await?;
+
+        timer.end();
+        timer_handle.await.map_err(Error::msg)?;
+        let filenames = filenames.lock().expect("not poisoned").clone();
+        Ok(filenames)
     }
 
     async fn run_helper(
         &self,
-        mut child: Child,
+        mut child: Box<dyn HashcatProcess>,
         log: &Logger,
+        total: u64,
+        runner: &str,
         is_bench: bool,
     ) -> Result<(Timer, Finished)> {
         // multiplier is how many derivations and seeds are performed per hash
         let mut multiplier = self.seed.hash_ratio();
-        multiplier *= self.address.derivations.hash_ratio();
-        let stderr = child.stderr.take();
+        multiplier *= self.derivation_ratio();
+        let multiplier = max(multiplier as u64, 1);
+        let stderr = child.take_stderr();
         spawn(Self::run_stderr(stderr, self.file(HC_ERROR_FILE)?));
         let timer = log
-            .time_verbose("Recovery Guesses", self.total(), multiplier as u64)
+            .time_verbose("Recovery Guesses", total, multiplier)
             .await;
-        let result = self.run_stdout(child, log, &timer, is_bench).await?;
-        let found = self.seed.found(result)?;
+        let exit = self.run_stdout(child, log, &timer, is_bench).await?;
+        let found = match exit {
+            HashcatExit::Found(results) => {
+                let mut founds = vec![];
+                for (address, result) in results {
+                    let found = self.seed.found(Some(result))?;
+                    // never lose a found seed because its derivation could not be checked
+                    match self.verify_found(found.clone(), &address) {
+                        Ok(verified) => founds.push(verified),
+                        Err(err) => {
+                            log.println_warn(&err.to_string());
+                            founds.push(found.with_unverified());
+                        }
+                    }
+                }
+                // verified matches are reported first
+                founds.sort_by_key(|found| found.unverified);
+                let first = founds.remove(0);
+                first.with_others(founds)
+            }
+            HashcatExit::Exhausted => self.seed.found(None)?,
+            HashcatExit::Stopped => {
+                // never claim 100% since some guesses were skipped
+                let percent = timer.count() as u128 * 100 / max(timer.total(), 1) as u128;
+                self.seed.found(None)?.with_stopped(min(percent as u64, 99))
+            }
+        };
+        let metrics = Self::metrics(&timer, total, multiplier, runner, &found);
+        // the metrics are optional so a disk error must not hide a found seed
+        if let Err(err) = self.write_metrics(&metrics) {
+            log.println_warn(&format!("Unable to write metrics: {}", err));
+        }
         self.exe.cd_seedcat();
         Ok((timer, found))
     }
 
+    /// Re-derives the address so a mismatch with the hashcat kernel isn't reported as success
+    fn verify_found(&self, mut found: Finished, address: &str) -> Result<Finished> {
+        if let (Some(seed), Some(passphrase)) = (&found.seed, &found.passphrase) {
+            let result = self.find_address(seed, passphrase, address);
+            let (address, derivation) = result.map_err(|err| {
+                format_err!(
+                    "Unverified match, hashcat reported seed '{}' with passphrase '{}' that does not derive your address, please report this bug\n{}",
+                    seed,
+                    passphrase,
+                    err
+                )
+            })?;
+            found = found.with_derivation(derivation);
+            // only report the address when guessing the address type
+            if self.addresses.len() > 1 {
+                found = found.with_address(Some(address.formatted.clone()));
+            }
+        }
+        Ok(found)
+    }
+
+    /// Finds which derivation path the found seed produced for the address hashcat reported
+    fn find_address(
+        &self,
+        seed: &str,
+        passphrase: &str,
+        reported: &str,
+    ) -> Result<(&AddressValid, Option<String>)> {
+        let mut err = None;
+        let reported: Vec<_> = self
+            .addresses
+            .iter()
+            .filter(|address| address.formatted == reported)
+            .collect();
+        // fallback to every address if hashcat formatted the address differently
+        let addresses = match reported.is_empty() {
+            true => self.addresses.iter().collect(),
+            false => reported,
+        };
+        for address in addresses {
+            match address.find_derivation(seed, passphrase) {
+                Ok(derivation) => return Ok((address, derivation)),
+                Err(e) => err = Some(e),
+            }
+        }
+        Err(err.expect("has addresses"))
+    }
+
+    /// JSON record of the run for comparing performance (excludes the seed and passphrase)
+    fn metrics(
+        timer: &Timer,
+        total: u64,
+        multiplier: u64,
+        runner: &str,
+        found: &Finished,
+    ) -> String {
+        let speed = timer.count() / timer.seconds();
+        let metrics = json!({
+            "mode": runner,
+            "total_guesses": total,
+            "guesses": timer.count(),
+            "speed": speed,
+            "gpu_speed": speed / multiplier,
+            "seconds": timer.seconds(),
+            "found": found.seed.is_some(),
+        });
+        serde_json::to_string_pretty(&metrics).expect("serializes")
+    }
+
+    fn write_metrics(&self, metrics: &str) -> Result<()> {
+        let mut file = self.file(HC_METRICS_FILE)?;
+        writeln!(file, "{}", metrics)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Absolute since hashcat may exit and change directory before the sender finishes
+    fn passphrases_file(&self) -> Option<PathBuf> {
+        // only worth caching if more than one seed replays the passphrases
+        if self.seed.total() <= 1 {
+            return None;
+        }
+        let name = self.prefix.clone() + HC_PASSPHRASES_FILE;
+        Some(env::current_dir().unwrap_or_default().join(name))
+    }
+
     fn hashfile(&self) -> String {
         format!("{}{}", self.prefix, HC_HASHES_FILE)
     }
@@ -283,25 +1009,28 @@ impl Hashcat {
         let path = Path::new(&hashfile);
         let file = File::create(path).unwrap();
         let writer = BufWriter::new(file);
-        let address = &self.address;
 
-        let mut parz: ParCompress<Gzip> = ParCompressBuilder::new().from_writer(writer);
-        let kind = address.kind.key.as_bytes();
+        let mut parz: ParCompress<Gzip> = ParCompressBuilder::new()
+            .compression_level(Compression::new(self.compression))
+            .from_writer(writer);
         let separator = ":".as_bytes();
-        let address = address.formatted.as_bytes();
         let newline = "\n".as_bytes();
 
         while let Some(seed) = receiver.recv().await {
-            for derivation in self.address.derivations.args() {
-                parz.write_all(kind).map_err(Error::msg)?;
-                parz.write_all(separator).map_err(Error::msg)?;
-                parz.write_all(derivation.as_bytes()).map_err(Error::msg)?;
-                parz.write_all(separator).map_err(Error::msg)?;
-                parz.write_all(&seed).map_err(Error::msg)?;
-                parz.write_all(separator).map_err(Error::msg)?;
-                parz.write_all(address).map_err(Error::msg)?;
-                parz.write_all(newline).map_err(Error::msg)?;
-                timer.add(1);
+            for address in &self.addresses {
+                let kind = address.kind.key.as_bytes();
+                for derivation in address.derivations.args() {
+                    parz.write_all(kind).map_err(Error::msg)?;
+                    parz.write_all(separator).map_err(Error::msg)?;
+                    parz.write_all(derivation.as_bytes()).map_err(Error::msg)?;
+                    parz.write_all(separator).map_err(Error::msg)?;
+                    parz.write_all(&seed).map_err(Error::msg)?;
+                    parz.write_all(separator).map_err(Error::msg)?;
+                    parz.write_all(address.formatted.as_bytes())
+                        .map_err(Error::msg)?;
+                    parz.write_all(newline).map_err(Error::msg)?;
+                    timer.add(1);
+                }
             }
         }
         parz.finish().map_err(Error::msg)?;
@@ -309,16 +1038,23 @@ impl Hashcat {
         timer_handle.await.map_err(Error::msg)
     }
 
-    fn spawn_hashcat(&self, args: &Vec<String>, mode: HashcatMode) -> Child {
+    fn spawn_hashcat(
+        &self,
+        args: &Vec<String>,
+        mode: HashcatMode,
+        log: &Logger,
+    ) -> Result<Box<dyn HashcatProcess>> {
         let mut cmd = self.exe.command();
         cmd.arg("-m");
-        cmd.arg("28510");
+        cmd.arg(self.hash_mode.to_string());
+        cmd.arg("--session");
+        cmd.arg(self.session());
         cmd.arg("-w");
         cmd.arg("4");
         cmd.arg("--status");
         cmd.arg("--self-test-disable");
         cmd.arg("--status-timer");
-        cmd.arg("1");
+        cmd.arg(log.status_secs().to_string());
         cmd.arg("--potfile-disable");
 
         // FIXME: Tuning is needed for faster status updates
@@ -326,64 +1062,75 @@ impl Hashcat {
         cmd.arg("-n");
         cmd.arg("1");
 
-        // -S mode is faster if we have <10M passphrases
-        if mode.is_pure_gpu() && mode.passphrases < S_MODE_MAXIMUM {
-            let attack_mode = self
-                .passphrase
-                .clone()
-                .map(|p| p.attack_mode)
-                .unwrap_or_default();
-            if attack_mode != 6 && attack_mode != 7 {
-                cmd.arg("-S");
-            }
+        if self.s_mode(&mode).0 {
+            cmd.arg("-S");
         }
         for arg in args {
             cmd.arg(arg);
         }
-        // println!("Running {:?}", cmd);
+        if let Ok(dir) = env::current_dir() {
+            log.println_debug(&format!("Working directory {}", dir.display()));
+        }
+        log.println_debug(&format!("Running {:?}", cmd));
 
-        cmd.stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .expect("Could not start hashcat process")
+        self.backend
+            .spawn(&mut cmd)
+            .map_err(|err| format_err!("Could not start hashcat process: {}\n{:?}", err, cmd))
     }
 
-    async fn stdin_sender(prefix: String, mut stdin: HashcatStdin, mut rx: Receiver<Vec<u8>>) {
+    async fn stdin_sender(
+        session: String,
+        mut stdin: HashcatStdin,
+        mut rx: Receiver<Vec<u8>>,
+    ) -> Result<()> {
         if stdin.passphrase_args.is_empty() {
             while let Some(seed) = rx.recv().await {
-                stdin.stdin_send(seed);
+                stdin.stdin_send(seed)?;
             }
         } else {
             let mut pass_buffer = vec![];
+            let mut cached = false;
             while let Some(seed) = rx.recv().await {
-                let mut pass_rx = Self::spawn_passphrases(&prefix, &stdin, &mut pass_buffer).await;
+                let mut pass_rx =
+                    Self::spawn_passphrases(&session, &stdin, &mut pass_buffer, cached).await?;
                 for pass in &pass_buffer {
                     let mut input = seed.clone();
                     input.extend_from_slice(pass);
-                    stdin.stdin_send(input);
+                    stdin.stdin_send(input)?;
                 }
                 while let Some(pass) = pass_rx.recv().await {
                     let mut input = seed.clone();
                     input.extend(pass);
-                    stdin.stdin_send(input);
+                    stdin.stdin_send(input)?;
                 }
+                // the file is removed if any passphrase could not be written
+                cached = stdin.tail_path.as_ref().is_some_and(|path| path.exists());
             }
         }
-        stdin.flush();
+        stdin.flush()
     }
 
     async fn spawn_passphrases(
-        prefix: &str,
+        session: &str,
         stdin: &HashcatStdin,
         buffer: &mut Vec<Vec<u8>>,
-    ) -> Receiver<Vec<u8>> {
+        cached: bool,
+    ) -> Result<Receiver<Vec<u8>>> {
         let (tx, mut rx) = channel(CHANNEL_SIZE);
 
         // all passphrases fit in memory
         let buffer_len = buffer.len();
-        if buffer_len > 0 && buffer_len < STDIN_PASSPHRASE_MEM {
-            return rx;
+        if buffer_len > 0 && buffer_len < stdin.passphrase_mem {
+            return Ok(rx);
+        }
+
+        // replay the passphrases after the buffer instead of regenerating them for every seed
+        if let (true, Some(path)) = (cached, &stdin.tail_path) {
+            let file = File::open(path).map_err(|err| {
+                format_err!("Unable to read passphrases '{}': {}", path.display(), err)
+            })?;
+            spawn(Self::send_lines(file, 0, None, stdin.passphrase_mem, tx));
+            return Ok(rx);
         }
 
         // spawn hashcat to stdout to generate passphrases
@@ -392,42 +1139,91 @@ impl Hashcat {
         let mut cmd = exe.command();
         cmd.arg("--stdout");
         cmd.arg("--session");
-        cmd.arg(format!("{}stdout", prefix));
+        cmd.arg(format!("{}stdout", session));
 
         for arg in passphrase_args {
             cmd.arg(arg);
         }
+        let mut child = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format_err!("Could not start hashcat process: {}\n{:?}", err, cmd))?;
+        let out = child.stdout.take().expect("Pipes stdout");
+        let running = Running::new(Box::new(child));
+        let mem = stdin.passphrase_mem;
+        // the first seed writes the passphrases that don't fit in memory for the later seeds
+        let tail = match (buffer_len, &stdin.tail_path) {
+            (0, Some(path)) => File::create(path).ok().map(|file| (file, path.clone())),
+            _ => None,
+        };
         spawn(async move {
-            let child = cmd
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .expect("Could not start hashcat process");
-            let out = child.stdout.expect("Pipes stdout");
-
-            let reader = BufReader::new(out);
-            let mut num = 0;
-            for read in reader.lines() {
-                num += 1;
-                if num > buffer_len {
-                    if tx.send(read.unwrap().into_bytes()).await.is_err() {
-                        break;
-                    }
-                }
-            }
+            // stops generating passphrases if the receiver is dropped
+            let _running = running;
+            Self::send_lines(out, buffer_len, tail, mem, tx).await;
         });
 
-        // initialize buffer
-        if buffer.len() == 0 {
-            while let Some(rx) = rx.recv().await {
-                if buffer.len() == STDIN_PASSPHRASE_MEM {
-                    break;
+        // initialize buffer without dropping the first passphrase after it
+        if buffer_len == 0 {
+            while buffer.len() < mem {
+                match rx.recv().await {
+                    Some(pass) => buffer.push(pass),
+                    None => break,
                 }
-                buffer.push(rx);
             }
         }
 
-        rx
+        Ok(rx)
+    }
+
+    /// Sends the lines after `skip`, copying those after the first `mem` lines into the `tail` file
+    async fn send_lines(
+        out: impl Read,
+        skip: usize,
+        tail: Option<(File, PathBuf)>,
+        mem: usize,
+        tx: Sender<Vec<u8>>,
+    ) {
+        let reader = BufReader::new(out);
+        let mut tail = tail.map(|(file, path)| (BufWriter::new(file), path));
+        let mut num = 0;
+        // passphrases may contain non-UTF8 bytes when using hex
+        for read in reader.split(b'\n') {
+            let mut line = read.unwrap();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            num += 1;
+            if num <= skip {
+                continue;
+            }
+            if num > mem {
+                Self::write_tail(&mut tail, &line);
+            }
+            if tx.send(line).await.is_err() {
+                break;
+            }
+        }
+        if let Some((mut file, path)) = tail {
+            if file.flush().is_err() {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    /// Stops caching the tail if it cannot be written completely
+    fn write_tail(tail: &mut Option<(BufWriter<File>, PathBuf)>, line: &[u8]) {
+        let Some((file, path)) = tail else {
+            return;
+        };
+        if file
+            .write_all(line)
+            .and_then(|_| file.write_all(b"\n"))
+            .is_err()
+        {
+            let _ = fs::remove_file(path);
+            *tail = None;
+        }
     }
 
     fn file(&self, name: &str) -> Result<BufWriter<File>> {
@@ -452,6 +1248,9 @@ impl Hashcat {
     }
 
     async fn spawn_seed_senders(&self) -> Receiver<Vec<u8>> {
+        if self.ordered {
+            return Self::spawn_ordered_senders(&self.seed).await;
+        }
         let (tx, rx) = channel(CHANNEL_SIZE);
         for shard in self.seed.shard_words(SEED_TASKS) {
             spawn(Self::seed_sender(shard, tx.clone()));
@@ -459,6 +1258,35 @@ impl Hashcat {
         rx
     }
 
+    /// Merges the shards in order, only generating a limited number of shards ahead
+    async fn spawn_ordered_senders(seed: &Seed) -> Receiver<Vec<u8>> {
+        let (tx, rx) = channel(CHANNEL_SIZE);
+        let mut shards = seed.shard_words(SEED_TASKS).into_iter();
+        spawn(async move {
+            let mut pending = VecDeque::new();
+            loop {
+                while pending.len() < ORDERED_TASKS {
+                    let Some(shard) = shards.next() else {
+                        break;
+                    };
+                    let (shard_tx, shard_rx) = channel(CHANNEL_SIZE);
+                    spawn(Self::seed_sender(shard, shard_tx));
+                    pending.push_back(shard_rx);
+                }
+                let Some(mut receiver) = pending.pop_front() else {
+                    break;
+                };
+                while let Some(next) = receiver.recv().await {
+                    if tx.send(next).await.is_err() {
+                        // receiver thread was killed
+                        return;
+                    }
+                }
+            }
+        });
+        rx
+    }
+
     async fn seed_sender(mut seed: Seed, sender: Sender<Vec<u8>>) {
         while let Some(next) = seed.next_valid() {
             if sender.send(next).await.is_err() {
@@ -470,17 +1298,25 @@ impl Hashcat {
 
     async fn run_stdout(
         &self,
-        mut child: Child,
+        mut child: Box<dyn HashcatProcess>,
         log: &Logger,
         timer: &Timer,
         is_bench: bool,
-    ) -> Result<Option<String>> {
+    ) -> Result<HashcatExit> {
         let mut handle = None;
+        let mut warned = false;
+        let mut stopped = false;
+        let mut reconciled = false;
+        let mut founds = vec![];
 
-        let address = self.address.formatted.clone();
         let mut file = self.file(HC_OUTPUT_FILE)?;
-        let out = child.stdout.take().expect("Pipes stdout");
-        let address = format!("{}:", address);
+        let out = child.take_stdout().expect("Pipes stdout");
+        let child = Running::new(child);
+        let addresses: Vec<_> = self
+            .addresses
+            .iter()
+            .map(|address| format!("{}:", address.formatted))
+            .collect();
         let reader = BufReader::new(out);
         log.println("Waiting for GPU initialization please be patient...".bold());
         for read in reader.lines() {
@@ -488,37 +1324,150 @@ impl Hashcat {
             if line.contains("* Device") && !line.contains("WARNING") && !line.contains("skipped") {
                 log.println(line.as_str().stylize());
             } else if line.starts_with("Time.Started.....: ") && handle.is_none() {
-                let num = line.split(" (").nth(1).unwrap();
-                let num = num.split(" sec").nth(0).unwrap();
-                let num = num.parse::<u64>().expect("is num");
-                handle = Some(timer.start_at(num).await);
+                // status output is cosmetic so unknown formats shouldn't stop the run
+                let secs = Self::parse_started(&line).unwrap_or_else(|| {
+                    Self::warn_status(log, &mut warned, &line);
+                    0
+                });
+                handle = Some(timer.start_at(secs).await);
             } else if line.starts_with("Progress.........: ") {
-                let num = line.split(": ").nth(1).unwrap();
-                let num = num.split("/").nth(0).unwrap();
-                let total = num.parse::<u64>().expect("is num");
-                timer.store(total);
-            } else if line.contains(&address) {
+                match Self::parse_progress(&line) {
+                    Some(total) => timer.store(total),
+                    None => Self::warn_status(log, &mut warned, &line),
+                }
+                // stdin mode has no keyspace to compare against
+                if let Some(total) = Self::parse_progress_total(&line) {
+                    Self::reconcile_total(log, timer, &mut reconciled, total);
+                }
+            } else if line.starts_with("Restore.Point....: ") {
+                // same '<count>/<total>' format as the progress
+                if let Some(point) = Self::parse_progress(&line) {
+                    self.resume.restore_point.store(point, Ordering::Relaxed);
+                }
+            } else if self.find_all && addresses.iter().any(|address| line.contains(address)) {
+                // hashcat removes the cracked hash line and keeps searching the others
+                if let Some((address, found)) = line.split_once(':') {
+                    let found = (address.to_string(), found.to_string());
+                    if !founds.contains(&found) {
+                        log.println("Found a match, searching for more...".dark_green().bold());
+                        founds.push(found);
+                    }
+                }
+                continue;
+            } else if addresses.iter().any(|address| line.contains(address)) {
                 child.kill().expect("can kill process");
                 timer.end();
                 if let Some(handle) = handle {
                     handle.await.expect("Logging finishes");
                 }
-                return Ok(line.split(":").nth(1).map(ToString::to_string));
+                // passphrases may contain ':' so only split off the address
+                let found = line
+                    .split_once(':')
+                    .map(|(address, found)| (address.to_string(), found.to_string()));
+                return Ok(found.map_or(HashcatExit::Exhausted, |f| HashcatExit::Found(vec![f])));
             } else if is_bench && timer.seconds() >= 60 {
+                stopped = true;
                 break;
             }
+            log.println_trace(&line);
             writeln!(file, "{}", line).map_err(Error::from)?;
             file.flush().map_err(Error::from)?;
         }
-        child.kill().expect("can kill process");
+        // benchmarks stop hashcat early so only a finished run has a meaningful exit code
+        let code = match stopped {
+            true => child.kill().map(|_| None),
+            // waiting blocks until hashcat exits so keep it off the async workers
+            false => spawn_blocking(move || child.wait())
+                .await
+                .map_err(Error::msg)?,
+        };
         timer.end();
         if let Some(handle) = handle {
             handle.await.expect("Logging finishes");
         }
-        Ok(None)
+        if !founds.is_empty() {
+            return Ok(HashcatExit::Found(founds));
+        }
+        let limited = self
+            .hashcat_args
+            .iter()
+            .any(|arg| arg == "-l" || arg.starts_with("--limit"));
+        match code.map_err(Error::from)? {
+            // hashcat exits with 0 if cracked and 1 if exhausted
+            Some(0) | Some(1) if !limited => Ok(HashcatExit::Exhausted),
+            // killed by a signal, stopped by the benchmark or only searching up to a '--limit'
+            Some(0) | Some(1) | None => Ok(HashcatExit::Stopped),
+            // aborted by the user, a checkpoint or the '--runtime' limit
+            Some(2..=4) => Ok(HashcatExit::Stopped),
+            Some(code) => bail!(
+                "Hashcat exited abnormally (code {}) so the search was not exhausted, see '{}{}' in the hashcat folder for the cause",
+                code,
+                self.prefix,
+                HC_ERROR_FILE
+            ),
+        }
+    }
+
+    /// Parses the seconds elapsed from 'Time.Started.....: <date> (<secs> secs)'
+    fn parse_started(line: &str) -> Option<u64> {
+        let num = line.split(" (").nth(1)?;
+        num.split(" sec").next()?.trim().parse::<u64>().ok()
+    }
+
+    /// Parses the count from 'Progress.........: <count>/<total> (<percent>%)'
+    fn parse_progress(line: &str) -> Option<u64> {
+        let num = line.split(": ").nth(1)?;
+        num.split("/").next()?.trim().parse::<u64>().ok()
+    }
+
+    /// Parses the total from 'Progress.........: <count>/<total> (<percent>%)'
+    fn parse_progress_total(line: &str) -> Option<u64> {
+        let num = line.split(": ").nth(1)?;
+        num.split("/")
+            .nth(1)?
+            .split(" (")
+            .next()?
+            .trim()
+            .parse::<u64>()
+            .ok()
+    }
+
+    /// Shows hashcat's keyspace as the total if it differs from our estimate (e.g. due to rules)
+    fn reconcile_total(log: &Logger, timer: &Timer, reconciled: &mut bool, hashcat_total: u64) {
+        if *reconciled {
+            return;
+        }
+        *reconciled = true;
+        let total = hashcat_total.saturating_mul(timer.multiplier());
+        if Self::is_mismatched(timer.total(), total) {
+            log.println_warn(&format!(
+                "Warning: Hashcat will try {} guesses but seedcat estimated {}, showing the progress of hashcat instead",
+                Logger::format_num(total),
+                Logger::format_num(timer.total())
+            ));
+            timer.set_total(total);
+        }
     }
 
-    async fn run_stderr(err: Option<ChildStderr>, mut file: BufWriter<File>) -> Result<()> {
+    fn is_mismatched(estimate: u64, actual: u64) -> bool {
+        let diff = estimate.abs_diff(actual) as u128;
+        diff * 100 > max(estimate, 1) as u128 * PROGRESS_MISMATCH_PERCENT as u128
+    }
+
+    fn warn_status(log: &Logger, warned: &mut bool, line: &str) {
+        if !*warned {
+            log.println_warn(&format!(
+                "Warning: Unable to parse hashcat status '{}'",
+                line
+            ));
+            *warned = true;
+        }
+    }
+
+    async fn run_stderr(
+        err: Option<Box<dyn Read + Send + Sync>>,
+        mut file: BufWriter<File>,
+    ) -> Result<()> {
         let err = err.expect("Piped stderr");
         let reader = BufReader::new(err);
         for read in reader.lines() {
@@ -531,58 +1480,709 @@ impl Hashcat {
 }
 
 struct HashcatStdin {
-    stdin: ChildStdin,
+    stdin: Box<dyn Write + Send + Sync>,
     stdin_buffer: Vec<u8>,
     passphrase_args: Vec<String>,
     exe: HashcatExe,
+    // Caches the passphrases that don't fit in memory, removed when dropped
+    tail_path: Option<PathBuf>,
+    // How many passphrases are kept in memory
+    passphrase_mem: usize,
+    // Files must be written completely unlike the pipe which closes when hashcat finds a match
+    files: bool,
 }
 
 impl HashcatStdin {
-    pub fn new(stdin: Option<ChildStdin>, passphrase_args: Vec<String>, exe: &HashcatExe) -> Self {
+    pub fn new(
+        stdin: Option<Box<dyn Write + Send + Sync>>,
+        passphrase_args: Vec<String>,
+        exe: &HashcatExe,
+        tail_path: Option<PathBuf>,
+    ) -> Self {
         Self {
             stdin: stdin.expect("Stdin piped"),
             stdin_buffer: vec![],
             passphrase_args,
             exe: exe.clone(),
+            tail_path,
+            passphrase_mem: STDIN_PASSPHRASE_MEM,
+            files: false,
         }
     }
 
-    fn stdin_send(&mut self, pass: Vec<u8>) {
+    /// Reports write errors instead of assuming hashcat closed the pipe
+    pub fn with_files(mut self) -> Self {
+        self.files = true;
+        self
+    }
+
+    fn stdin_send(&mut self, pass: Vec<u8>) -> Result<()> {
         self.stdin_buffer.extend(pass);
         self.stdin_buffer.push(10); // terminate password
         if self.stdin_buffer.len() > STDIN_BUFFER_BYTES {
             // might close when we find a match
-            let _ = self.stdin.write_all(&self.stdin_buffer);
+            let result = self.stdin.write_all(&self.stdin_buffer);
             self.stdin_buffer.clear();
+            self.check(result)?;
         }
+        Ok(())
     }
 
-    pub fn flush(&mut self) {
+    pub fn flush(&mut self) -> Result<()> {
         // might close early due to success
-        let _ = self.stdin.write_all(&self.stdin_buffer);
-        let _ = self.stdin.flush();
+        let result = self.stdin.write_all(&self.stdin_buffer);
+        self.check(result)?;
+        let result = self.stdin.flush();
+        self.check(result)
+    }
+
+    fn check(&self, result: io::Result<()>) -> Result<()> {
+        match result {
+            Err(err) if self.files => bail!("Unable to write candidates: {}", err),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for HashcatStdin {
+    fn drop(&mut self) {
+        // the cache can be several GB so remove it even if hashcat stops early
+        if let Some(path) = &self.tail_path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Rotates the candidates into gzip files that hashcat reads as dictionaries
+struct CandidateFiles {
+    // Path without the file number and extension
+    path: PathBuf,
+    max_lines: u64,
+    lines: u64,
+    compression: u32,
+    writer: Option<ParCompress<Gzip>>,
+    filenames: Arc<Mutex<Vec<PathBuf>>>,
+    timer: Timer,
+}
+
+impl CandidateFiles {
+    fn new(path: PathBuf, max_lines: u64, compression: u32, timer: &Timer) -> Self {
+        Self {
+            path,
+            max_lines,
+            lines: 0,
+            compression,
+            writer: None,
+            filenames: Arc::new(Mutex::new(vec![])),
+            timer: timer.clone(),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.finish().map_err(io::Error::other)?;
+        }
+        self.lines = 0;
+        Ok(())
+    }
+}
+
+impl Write for CandidateFiles {
+    // HashcatStdin only writes whole lines so files rotate between writes
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.lines >= self.max_lines {
+            self.finish()?;
+        }
+        if self.writer.is_none() {
+            let mut filenames = self.filenames.lock().expect("not poisoned");
+            let path = format!("{}{}.gz", self.path.display(), filenames.len() + 1);
+            let file = File::create(&path)?;
+            filenames.push(PathBuf::from(path));
+            self.writer = Some(
+                ParCompressBuilder::new()
+                    .compression_level(Compression::new(self.compression))
+                    .from_writer(BufWriter::new(file)),
+            );
+        }
+        let writer = self.writer.as_mut().expect("file created");
+        writer.write_all(buf)?;
+        let lines = buf.iter().filter(|b| **b == b'\n').count() as u64;
+        self.lines += lines;
+        self.timer.add(lines);
+        Ok(buf.len())
+    }
+
+    // only flushed once all candidates are written so the gzip file is finished
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::backend::mock::MockBackend;
     use crate::hashcat::*;
+    use serde_json::Value;
+
+    #[test]
+    fn previews_candidates() {
+        let seed = "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?";
+        let wordlist = crate::seed::bip39_wordlist();
+        let words = Seed::checksum_words(&seed.replace(",?", ""), &wordlist).unwrap();
+        let hc = hashcat("a?d", seed);
+        let preview = hc.preview(12).unwrap();
+        assert_eq!(preview.len(), 12);
+        assert_eq!(
+            preview[0],
+            format!("{},{} a0", seed.replace(",?", ""), words[0])
+        );
+        assert_eq!(
+            preview[11],
+            format!("{},{} a1", seed.replace(",?", ""), words[1])
+        );
+    }
+
+    #[test]
+    fn removes_temp_files() {
+        File::create("hc_interrupt_hashes.gz").unwrap();
+        File::create("hc_interrupt_error.log").unwrap();
+        File::create("hc_interrupted.log").unwrap();
+        Hashcat::remove_temp_files(Path::new("."), "hc_interrupt_");
+        assert!(!Path::new("hc_interrupt_hashes.gz").exists());
+        assert!(!Path::new("hc_interrupt_error.log").exists());
+        assert!(Path::new("hc_interrupted.log").exists());
+        std::fs::remove_file("hc_interrupted.log").unwrap();
+    }
 
     fn hashcat(passphrase: &str, seed: &str) -> Hashcat {
-        let passphrase = Passphrase::from_arg(&vec![passphrase.to_string()], &vec![]).unwrap();
-        let seed = Seed::from_args(seed, &None).unwrap();
         let derivation = Some("m/0/0".to_string());
         let address =
             AddressValid::from_arg("1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9", &derivation).unwrap();
+        hashcat_address(address, passphrase, seed)
+    }
+
+    fn hashcat_address(address: AddressValid, passphrase: &str, seed: &str) -> Hashcat {
+        let passphrase = Passphrase::from_arg(&vec![passphrase.to_string()], &vec![]).unwrap();
+        let seed = Seed::from_args(seed, &None).unwrap();
         Hashcat::new(
             HashcatExe::new(PathBuf::new()),
-            address,
+            vec![address],
             seed.clone(),
             Some(passphrase.clone()),
             vec![],
         )
     }
 
+    #[test]
+    fn resolves_directories() {
+        let exe = HashcatExe::new(PathBuf::from("/seedcat/hashcat/hashcat.bin"));
+        assert_eq!(exe.hashcat_dir(), Path::new("/seedcat/hashcat"));
+        assert_eq!(exe.seedcat_dir(), Path::new("/seedcat"));
+
+        let mut hc = hashcat("", "zoo");
+        hc.exe = exe;
+        assert_eq!(hc.temp_files(), PathBuf::from("/seedcat/hashcat/hc_*"));
+    }
+
+    #[test]
+    fn parses_devices() {
+        let output = "CUDA Info:\n\
+                      Backend Device ID #1 (Alias: #2)\n\
+                      \x20 Name...........: NVIDIA GeForce RTX 4090\n\
+                      OpenCL Info:\n\
+                      \x20 Backend Device ID #2 (Alias: #1)\n\
+                      \x20   Type...........: GPU\n\
+                      \x20   Name...........: NVIDIA GeForce RTX 4090\n\
+                      \x20 Backend Device ID #3\n\
+                      \x20   Type...........: CPU\n\
+                      \x20   Name...........: cpu-haswell-AMD Ryzen 9\n";
+        let devices = HashcatExe::parse_devices(output);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].id, "1");
+        assert_eq!(devices[0].name, "NVIDIA GeForce RTX 4090");
+        assert!(!devices[0].is_cpu);
+        assert_eq!(devices[1].name, "cpu-haswell-AMD Ryzen 9");
+        assert!(devices[1].is_cpu);
+        assert!(HashcatExe::parse_devices("").is_empty());
+    }
+
+    #[test]
+    fn parses_benchmark_speed() {
+        let output = "Hashmode: 28510 - BIP39 Seed\n\
+                      Speed.#1.........:   187.5 kH/s (51.20ms) @ Accel:8 Loops:1024 Thr:64 Vec:1\n\
+                      Speed.#2.........:   12500 H/s (62.11ms) @ Accel:8 Loops:1024 Thr:64 Vec:1\n";
+        assert_eq!(HashcatExe::parse_speed(output), Some(200_000));
+
+        let output = format!("{}Speed.#*.........:  1.25 MH/s\n", output);
+        assert_eq!(HashcatExe::parse_speed(&output), Some(1_250_000));
+
+        assert_eq!(HashcatExe::parse_speed("Started: Mon\n"), None);
+        assert_eq!(HashcatExe::parse_speed("Speed.#1.........: fast\n"), None);
+    }
+
+    #[tokio::test]
+    async fn orders_seed_senders() {
+        let mut hc = hashcat("", "ab?,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?");
+        hc.set_ordered(true);
+        let mut first = vec![];
+        let mut rx = hc.spawn_seed_senders().await;
+        while let Some(next) = rx.recv().await {
+            first.push(next);
+        }
+        let mut second = vec![];
+        let mut rx = hc.spawn_seed_senders().await;
+        while let Some(next) = rx.recv().await {
+            second.push(next);
+        }
+        assert_eq!(first, second);
+        assert_eq!(first.len() as u64, hc.seed.valid_seeds());
+
+        hc.set_ordered(false);
+        let mut unordered = vec![];
+        let mut rx = hc.spawn_seed_senders().await;
+        while let Some(next) = rx.recv().await {
+            unordered.push(next);
+        }
+        unordered.sort();
+        first.sort();
+        assert_eq!(first, unordered);
+    }
+
+    #[tokio::test]
+    async fn writes_candidate_files() {
+        let read = |path: &PathBuf| {
+            let mut out = vec![];
+            let file = File::open(path).unwrap();
+            let mut decoder = flate2::read::MultiGzDecoder::new(file);
+            decoder.read_to_end(&mut out).unwrap();
+            fs::remove_file(path).unwrap();
+            out
+        };
+        let timer = Logger::off().time("", 3).await;
+        let path = PathBuf::from("hc_rotates_candidates");
+        let mut files = CandidateFiles::new(path, 2, 1, &timer);
+        files.write_all(b"a\nb\n").unwrap();
+        files.write_all(b"c\n").unwrap();
+        files.flush().unwrap();
+        let filenames = files.filenames.lock().unwrap().clone();
+        assert_eq!(filenames.len(), 2);
+        assert_eq!(read(&filenames[0]), b"a\nb\n");
+        assert_eq!(read(&filenames[1]), b"c\n");
+        assert_eq!(timer.count(), 3);
+
+        let seed = "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?";
+        let mut hc = hashcat("", seed);
+        hc.set_prefix("hc_candidate_files".to_string());
+        hc.passphrase = None;
+        let seed_rx = hc.spawn_seed_senders().await;
+        let filenames = hc
+            .write_candidate_files(seed_rx, vec![], &Logger::off())
+            .await
+            .unwrap();
+        assert_eq!(filenames.len(), 1);
+        // candidates are encoded bytes so only count the lines
+        let lines = read(&filenames[0]).iter().filter(|b| **b == b'\n').count();
+        assert_eq!(lines, 128);
+    }
+
+    #[tokio::test]
+    async fn sends_passphrase_lines() {
+        let (tx, mut rx) = channel(10);
+        let out = std::io::Cursor::new(b"a\nb\r\n\xffc\n".to_vec());
+        Hashcat::send_lines(out, 1, None, STDIN_PASSPHRASE_MEM, tx).await;
+        let mut lines = vec![];
+        while let Some(line) = rx.recv().await {
+            lines.push(line);
+        }
+        assert_eq!(lines, vec![b"b".to_vec(), b"\xffc".to_vec()]);
+
+        // only passphrases that don't fit in memory are cached
+        let (tx, mut rx) = channel(10);
+        let out = std::io::Cursor::new(b"a\nb\r\n\xffc\n".to_vec());
+        let path = PathBuf::from("hc_sends_passphrase_lines.txt");
+        let tail = Some((File::create(&path).unwrap(), path.clone()));
+        Hashcat::send_lines(out, 0, tail, 1, tx).await;
+        let mut generated = vec![];
+        while let Some(line) = rx.recv().await {
+            generated.push(line);
+        }
+
+        // later seeds replay the cache after the lines in memory
+        let (tx, mut rx) = channel(10);
+        let file = File::open(&path).unwrap();
+        Hashcat::send_lines(file, 0, None, 1, tx).await;
+        let mut replayed = vec![generated[0].clone()];
+        while let Some(line) = rx.recv().await {
+            replayed.push(line);
+        }
+        fs::remove_file(&path).unwrap();
+        assert_eq!(generated.len(), 3);
+        assert_eq!(replayed, generated);
+    }
+
+    #[tokio::test]
+    async fn writes_metrics() {
+        let timer = Logger::off().time_verbose("", 100, 4).await;
+        timer.store(25);
+        let found = Finished::new("zoo", "", false);
+        let metrics = Hashcat::metrics(&timer, 100, 4, "stdin_max_hashes", &found);
+        let expected = json!({
+            "mode": "stdin_max_hashes",
+            "total_guesses": 100,
+            "guesses": 100,
+            "speed": 100,
+            "gpu_speed": 25,
+            "seconds": 1,
+            "found": true,
+        });
+        assert_eq!(serde_json::from_str::<Value>(&metrics).unwrap(), expected);
+        let metrics = Hashcat::metrics(&timer, 100, 4, "pure_gpu", &Finished::exhausted(true));
+        assert!(metrics.contains("\"found\": false"));
+        assert!(!metrics.contains("zoo"));
+    }
+
+    #[test]
+    fn breaks_down_total_guesses() {
+        let mut hc = hashcat("?d?d", "zoo,?");
+        assert_eq!(
+            hc.total_breakdown(),
+            "2.05K seeds x 1 derivations x 100 passphrases"
+        );
+
+        let then = Passphrase::from_arg(&vec!["?d".to_string()], &vec![]).unwrap();
+        hc.set_then_passphrases(vec![then]);
+        assert_eq!(
+            hc.total_breakdown(),
+            "2.05K seeds x 1 derivations x 110 passphrases"
+        );
+        assert_eq!(hc.total(), 2048 * 110);
+    }
+
+    #[test]
+    fn guesses_every_address() {
+        let mut hc = hashcat("", "zoo,?");
+        let one = hc.total();
+        let derivation = Some("m/0/0,m/84h/0h/0h/0/0".to_string());
+        hc = Hashcat::new(
+            HashcatExe::new(PathBuf::new()),
+            vec![
+                AddressValid::from_arg("1111111111111111111114oLvT2", &derivation).unwrap(),
+                AddressValid::from_arg("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu", &derivation)
+                    .unwrap(),
+            ],
+            hc.seed.clone(),
+            hc.passphrase.clone(),
+            vec![],
+        );
+        assert_eq!(hc.total(), one * 4);
+        assert_eq!(hc.derivation_args(), 2);
+
+        let seed = "abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,abandon,about";
+        let (address, derivation) = hc.find_address(seed, "", "").unwrap();
+        assert_eq!(address.kind.key, "P2WPKH");
+        assert_eq!(derivation, Some("m/84h/0h/0h/0/0".to_string()));
+        assert!(hc.find_address(seed, "wrong", "").is_err());
+        let reported = "1111111111111111111114oLvT2";
+        assert!(hc.find_address(seed, "", reported).is_err());
+        let reported = "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu";
+        let found = hc
+            .verify_found(Finished::new(seed, "", false), reported)
+            .unwrap();
+        assert_eq!(found.address, Some(reported.to_string()));
+        let unverified = Finished::new(seed, "wrong", false);
+        let err = hc
+            .verify_found(unverified, reported)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(&format!("seed '{}' with passphrase 'wrong'", seed)));
+
+        let found = Finished::new(seed, "", false).with_address(Some(address.formatted.clone()));
+        assert_eq!(hc.found_address(&found).kind.key, "P2WPKH");
+    }
+
+    #[test]
+    fn chooses_s_mode() {
+        let mut hc = hashcat("?d?d?d?d", "?,?,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo");
+        let pure = HashcatMode::new(HashcatRunner::PureGpu, 10_000, 1);
+        let stdin = HashcatMode::new(HashcatRunner::StdinMaxHashes, 0, 1);
+        let many = HashcatMode::new(HashcatRunner::PureGpu, S_MODE_MAXIMUM, 1);
+        assert!(hc.s_mode(&pure).0);
+        assert!(!hc.s_mode(&stdin).0);
+        assert!(!hc.s_mode(&many).0);
+
+        hc.set_s_mode(Some(false));
+        assert_eq!(hc.s_mode(&pure), (false, "disabled by --no-single-mode"));
+        hc.set_s_mode(Some(true));
+        assert_eq!(hc.s_mode(&many), (true, "forced by --single-mode"));
+
+        let args = vec!["asdf".to_string(), "?d".to_string()];
+        hc.passphrase = Some(Passphrase::from_arg(&args, &vec![]).unwrap());
+        hc.set_s_mode(None);
+        assert_eq!(hc.s_mode(&pure), (false, "hybrid passphrase attack"));
+    }
+
+    #[test]
+    fn names_sessions() {
+        let mut hc = hashcat("", "zoo");
+        assert_eq!(hc.session(), format!("hc{}", process::id()));
+        hc.set_prefix("hc_test".to_string());
+        assert_eq!(hc.session(), format!("hc_test{}", process::id()));
+        hc.set_session("recovery".to_string());
+        assert_eq!(hc.session(), "recovery");
+    }
+
+    #[tokio::test]
+    async fn parses_mock_hashcat_output() {
+        let mut hc = hashcat("", "zoo,?");
+        hc.set_prefix("hc_mock".to_string());
+        let mode = HashcatMode::new(HashcatRunner::StdinMaxHashes, 0, 1);
+        let stdout = "* Device #1: Mock GPU\n\
+                      Time.Started.....: Mon (2 secs)\n\
+                      Progress.........: 10/20 (50.00%)\n\
+                      1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9:ability,pass:word\n";
+        hc.set_backend(Arc::new(MockBackend::new(stdout, "")));
+        let timer = Logger::off().time("", 20).await;
+        let child = hc
+            .spawn_hashcat(&vec![], mode.clone(), &Logger::off())
+            .unwrap();
+        let exit = hc.run_stdout(child, &Logger::off(), &timer, false).await;
+        let address = "1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9".to_string();
+        let found = HashcatExit::Found(vec![(address.clone(), "ability,pass:word".to_string())]);
+        assert_eq!(exit.unwrap(), found);
+
+        let found = "1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9:ability,one\n\
+                     1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9:ability,two\n\
+                     1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9:ability,one\n";
+        hc.set_backend(Arc::new(MockBackend::new(found, "")));
+        hc.set_find_all(true);
+        let timer = Logger::off().time("", 20).await;
+        let child = hc
+            .spawn_hashcat(&vec![], mode.clone(), &Logger::off())
+            .unwrap();
+        let exit = hc.run_stdout(child, &Logger::off(), &timer, false).await;
+        let found = vec![
+            (address.clone(), "ability,one".to_string()),
+            (address.clone(), "ability,two".to_string()),
+        ];
+        assert_eq!(exit.unwrap(), HashcatExit::Found(found));
+
+        // the same seed can control several of the addresses
+        let other = "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu".to_string();
+        let mut addresses = hc.addresses.clone();
+        addresses.push(AddressValid::from_arg(&other, &None).unwrap());
+        let mut both = Hashcat::new(
+            HashcatExe::new(PathBuf::new()),
+            addresses,
+            hc.seed.clone(),
+            hc.passphrase.clone(),
+            vec![],
+        );
+        both.set_prefix("hc_mock".to_string());
+        both.set_find_all(true);
+        let found = format!("{}:ability,one\n{}:ability,one\n", address, other);
+        both.set_backend(Arc::new(MockBackend::new(&found, "")));
+        let timer = Logger::off().time("", 20).await;
+        let child = both
+            .spawn_hashcat(&vec![], mode.clone(), &Logger::off())
+            .unwrap();
+        let exit = both.run_stdout(child, &Logger::off(), &timer, false).await;
+        let found = vec![
+            (address.clone(), "ability,one".to_string()),
+            (other, "ability,one".to_string()),
+        ];
+        assert_eq!(exit.unwrap(), HashcatExit::Found(found));
+        hc.set_find_all(false);
+
+        hc.set_backend(Arc::new(MockBackend::new("Progress.........: 20/20\n", "")));
+        let timer = Logger::off().time("", 20).await;
+        let child = hc
+            .spawn_hashcat(&vec![], mode.clone(), &Logger::off())
+            .unwrap();
+        let exit = hc.run_stdout(child, &Logger::off(), &timer, false).await;
+        assert_eq!(exit.unwrap(), HashcatExit::Exhausted);
+        assert_eq!(timer.count(), 20);
+
+        let stopped = "Restore.Point....: 8/20 (40.00%)\nProgress.........: 10/20\n";
+        hc.set_backend(Arc::new(MockBackend::new(stopped, "").with_code(1)));
+        hc.hashcat_args = vec!["--limit=10".to_string()];
+        hc.set_resume_args(vec!["seedcat".to_string(), "-y".to_string()]);
+        hc.resume.resumable.store(true, Ordering::Relaxed);
+        let timer = Logger::off().time("", 20).await;
+        let child = hc
+            .spawn_hashcat(&vec![], mode.clone(), &Logger::off())
+            .unwrap();
+        let exit = hc.run_stdout(child, &Logger::off(), &timer, false).await;
+        assert_eq!(exit.unwrap(), HashcatExit::Stopped);
+        assert_eq!(hc.resume_command().unwrap(), "seedcat -y -- --skip 8");
+        hc.hashcat_args = vec![];
+
+        let aborted = MockBackend::new("Progress.........: 10/20\n", "").with_code(4);
+        hc.set_backend(Arc::new(aborted));
+        let timer = Logger::off().time("", 20).await;
+        let child = hc
+            .spawn_hashcat(&vec![], mode.clone(), &Logger::off())
+            .unwrap();
+        let exit = hc.run_stdout(child, &Logger::off(), &timer, false).await;
+        assert_eq!(exit.unwrap(), HashcatExit::Stopped);
+
+        let backend = MockBackend::new("Progress.........: 10/20\n", "").with_code(255);
+        hc.set_backend(Arc::new(backend));
+        let timer = Logger::off().time("", 20).await;
+        let child = hc.spawn_hashcat(&vec![], mode, &Logger::off()).unwrap();
+        let err = hc.run_stdout(child, &Logger::off(), &timer, false).await;
+        let err = err.err().unwrap().to_string();
+        assert!(err.contains("Hashcat exited abnormally (code 255)"));
+        assert!(err.contains("hc_mock_error.log"));
+        std::fs::remove_file("hc_mock_output.log").unwrap();
+    }
+
+    #[test]
+    fn builds_resume_commands() {
+        let args = |args: &str| {
+            args.split(' ')
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+        };
+        let resume = Resume::with_skip(&args("seedcat -s zoo,?"), 5);
+        assert_eq!(resume, args("seedcat -s zoo,? -- --skip 5"));
+        let resume = Resume::with_skip(&args("seedcat -- -w 4 -s 10 --skip=20 --restore"), 30);
+        assert_eq!(resume, args("seedcat -- -w 4 --skip 30"));
+
+        assert_eq!(Resume::quote("m/0/0,zoo"), "m/0/0,zoo");
+        assert_eq!(Resume::quote("zoo ?"), "'zoo ?'");
+        assert_eq!(Resume::quote("it's"), "'it'\\''s'");
+        assert_eq!(Resume::quote(""), "''");
+
+        let resume = Resume::default();
+        resume.restore_point.store(5, Ordering::Relaxed);
+        assert_eq!(resume.command(), None);
+        let resume = Resume {
+            args: Some(args("seedcat -p ?d")),
+            ..Default::default()
+        };
+        resume.restore_point.store(5, Ordering::Relaxed);
+        assert_eq!(resume.command(), None);
+        resume.resumable.store(true, Ordering::Relaxed);
+        assert_eq!(resume.command().unwrap(), "seedcat -p '?d' -- --skip 5");
+    }
+
+    #[test]
+    fn parses_status_lines() {
+        let started = "Time.Started.....: Mon Jan  1 00:00:00 2024 (12 secs)";
+        assert_eq!(Hashcat::parse_started(started), Some(12));
+        assert_eq!(Hashcat::parse_started("Time.Started.....: 0 sec"), None);
+        assert_eq!(
+            Hashcat::parse_started("Time.Started.....: Mon (1 min)"),
+            None
+        );
+
+        let progress = "Progress.........: 1024/2048 (50.00%)";
+        assert_eq!(Hashcat::parse_progress(progress), Some(1024));
+        assert_eq!(
+            Hashcat::parse_progress("Progress.........: 1.024/2.048"),
+            None
+        );
+        assert_eq!(Hashcat::parse_progress("Progress.........:"), None);
+        assert_eq!(Hashcat::parse_progress_total(progress), Some(2048));
+        assert_eq!(
+            Hashcat::parse_progress_total("Progress.........: 1024"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn reconciles_progress_total() {
+        assert!(!Hashcat::is_mismatched(1000, 1099));
+        assert!(!Hashcat::is_mismatched(1000, 901));
+        assert!(Hashcat::is_mismatched(1000, 1101));
+        assert!(Hashcat::is_mismatched(1000, 899));
+        assert!(Hashcat::is_mismatched(0, 1));
+
+        let log = Logger::off();
+        let timer = log.time_verbose("", 1000, 2).await;
+        let mut reconciled = false;
+        Hashcat::reconcile_total(&log, &timer, &mut reconciled, 500);
+        assert_eq!(timer.total(), 1000);
+        assert!(reconciled);
+
+        let timer = log.time_verbose("", 1000, 2).await;
+        let mut reconciled = false;
+        Hashcat::reconcile_total(&log, &timer, &mut reconciled, 1000);
+        assert_eq!(timer.total(), 2000);
+        // only checked against the first progress line
+        Hashcat::reconcile_total(&log, &timer, &mut reconciled, 5000);
+        assert_eq!(timer.total(), 2000);
+    }
+
+    #[test]
+    fn reports_spawn_failures() {
+        let hc = hashcat("", "zoo");
+        let mode = HashcatMode::new(HashcatRunner::StdinMaxHashes, 0, 1);
+        let err = hc
+            .spawn_hashcat(&vec![], mode, &Logger::off())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Could not start hashcat process"));
+    }
+
+    #[tokio::test]
+    async fn writes_candidates() {
+        let seed = "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?";
+        let mut hc = hashcat("", seed);
+        hc.set_prefix("hc_candidates".to_string());
+        hc.passphrase = None;
+        let out = File::create("hc_candidates.txt").unwrap();
+        hc.send_candidates(Box::new(out), vec![], &Logger::off())
+            .await
+            .unwrap();
+
+        let candidates = std::fs::read("hc_candidates.txt").unwrap();
+        let lines: Vec<_> = candidates.split(|b| *b == b'\n').collect();
+        // 128 valid checksums each encoding 7 bits of entropy in 1 byte
+        assert_eq!(lines.len(), 129);
+        assert_eq!(lines[0], vec![48 + 7]);
+        assert_eq!(lines[127], vec![48 + 7 + 127]);
+        std::fs::remove_file("hc_candidates.txt").unwrap();
+        std::fs::remove_file("hc_candidates_hashes.gz").unwrap();
+    }
+
+    #[test]
+    fn forces_pure_gpu_for_known_seeds() {
+        let seed = "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,wrong";
+        let mut hc = hashcat("?d", seed);
+        assert!(matches!(
+            hc.get_mode().unwrap().runner,
+            HashcatRunner::StdinMinPassphrases
+        ));
+        hc.force_pure_gpu();
+        let mode = hc.get_mode().unwrap();
+        assert!(matches!(mode.runner, HashcatRunner::PureGpu));
+        assert_eq!(mode.passphrases, 10);
+        assert_eq!(mode.hashes, 1);
+    }
+
+    #[test]
+    fn keeps_newline_bytes_out_of_stdin() {
+        let seed = "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,wrong";
+        let mut hc = hashcat("?b", seed);
+        let mode = hc.get_mode().unwrap();
+        assert!(matches!(mode.runner, HashcatRunner::PureGpu));
+        assert_eq!(mode.passphrases, 256);
+
+        hc.max_hashes = 0;
+        let err = hc.get_mode().err().unwrap().to_string();
+        assert!(err.contains("cannot be sent to hashcat over stdin"));
+    }
+
+    #[test]
+    fn totals_chained_passphrases() {
+        let mut hc = hashcat("?d", "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?");
+        assert_eq!(hc.total(), 2048 * 10);
+        let then = Passphrase::from_arg(&vec!["?d?d".to_string()], &vec![]).unwrap();
+        hc.set_then_passphrases(vec![then.clone(), then]);
+        assert_eq!(hc.total(), 2048 * 10 + 2048 * 100 * 2);
+    }
+
     #[test]
     fn determines_whether_to_run_pure_gpu() {
         let hc = hashcat("", "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?,?");
@@ -622,4 +2222,31 @@ mod tests {
         assert_eq!(mode.passphrases, 0);
         assert_eq!(hc.total(), 10_000 * 2048 * 2048 * 2048);
     }
+
+    #[test]
+    fn runs_master_xpubs_without_derivations() {
+        let xpub = "xpub661MyMwAqRbcG95rS28rhZiknMvbUJhPpEWgMUbWa4xjMEc12aVewXf7fey3rGD9Sef81NXqTd1vyYToRokkiU9BTz6u5UXmikfNHTV9oCT";
+        let seed = "?,?,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo";
+        let address = AddressValid::from_arg(xpub, &None).unwrap();
+        let hc = hashcat_address(address, "?d?d?d?d", seed);
+        assert_eq!(hc.derivation_args(), 1);
+        assert_eq!(hc.total(), 10_000 * 2048 * 2048);
+        let mode = hc.get_mode().unwrap();
+        assert!(matches!(mode.runner, HashcatRunner::PureGpu));
+        assert_eq!(mode.hashes, (2048 * 2048) / 16);
+        assert_eq!(mode.passphrases, 10_000);
+
+        // addresses derive every default path for each guess
+        let address = AddressValid::from_arg("1B2hrNm7JGW6Wenf8oMvjWB3DPT9H9vAJ9", &None).unwrap();
+        let derivations = address.derivations.total();
+        assert!(derivations > 1);
+        let hc = hashcat_address(address, "?d?d?d?d", seed);
+        assert_eq!(hc.total(), derivations * 10_000 * 2048 * 2048);
+        assert_eq!(hc.derivation_ratio(), derivations as f64);
+
+        let hc = hashcat_address(AddressValid::from_arg(xpub, &None).unwrap(), "", seed);
+        let mode = hc.get_mode().unwrap();
+        assert!(matches!(mode.runner, HashcatRunner::StdinMinPassphrases));
+        assert_eq!(mode.hashes, 1);
+    }
 }
@@ -1,5 +1,5 @@
 use std::cmp::max;
-use std::io::{stdout, Write};
+use std::io::{stderr, stdout, IsTerminal, Write};
 use std::ops::Sub;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -26,10 +26,56 @@ const MINUTE: u64 = 60;
 const HOUR: u64 = MINUTE * 60;
 const DAY: u64 = HOUR * 24;
 
+/// Severity of log output, each level also prints every level before it
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// Level from the number of `-v` flags, falling back to `RUST_LOG` if there are none
+    pub fn from_args(verbose: u8, rust_log: Option<String>) -> Result<Self> {
+        match verbose {
+            0 => {}
+            1 => return Ok(LogLevel::Debug),
+            _ => return Ok(LogLevel::Trace),
+        }
+        let rust_log = match rust_log {
+            None => return Ok(LogLevel::Info),
+            Some(rust_log) => rust_log.trim().to_lowercase(),
+        };
+        // only the global level applies e.g. 'warn' in 'warn,hyper=info'
+        let level = rust_log
+            .split(',')
+            .map(str::trim)
+            .find(|directive| !directive.contains('='))
+            .unwrap_or("");
+        match level {
+            "" | "info" => Ok(LogLevel::Info),
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => bail!(
+                "RUST_LOG '{}' should be one of error, warn, info, debug or trace",
+                rust_log
+            ),
+        }
+    }
+}
+
 /// Logger that can be either off or on
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Logger {
     is_logging: bool,
+    level: LogLevel,
+    status_interval: Duration,
+    // Appends a line per progress update instead of redrawing with cursor movement
+    plain_progress: bool,
 }
 
 /// Formats table headings and rows
@@ -107,6 +153,21 @@ impl Timer {
         self.counter.store(amt, Ordering::Relaxed);
     }
 
+    /// Get the total the progress is measured against
+    pub fn total(&self) -> u64 {
+        self.total.fetch_add(0, Ordering::Relaxed)
+    }
+
+    /// Replace the total if it was estimated incorrectly
+    pub fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Get how many guesses each counted item represents
+    pub fn multiplier(&self) -> u64 {
+        self.multiplier
+    }
+
     /// Tell the timer loop to end
     pub fn end(&self) {
         self.end.store(1, Ordering::Relaxed);
@@ -124,6 +185,7 @@ impl Timer {
         spawn(async move {
             let now = Instant::now().sub(Duration::from_secs(secs));
             let mut old_count = u64::MAX;
+            let mut last_print: Option<Instant> = None;
             let name = timer.name.as_str().bold();
 
             loop {
@@ -137,7 +199,14 @@ impl Timer {
                 }
                 let total = timer.total.fetch_add(0, Ordering::Relaxed);
 
-                if !timer.oneliner && old_count == u64::MAX {
+                // Wait for the status interval unless we are finished
+                let waiting = last_print.map(|p| p.elapsed() < timer.log.status_interval);
+                if waiting == Some(true) && count < total && end == 0 {
+                    continue;
+                }
+                last_print = Some(Instant::now());
+
+                if !timer.oneliner && !timer.log.plain_progress && old_count == u64::MAX {
                     timer.log.println("\n\n\n\n\n".stylize());
                 }
 
@@ -166,30 +235,55 @@ impl Timer {
                     progress, speed, gpu, eta, elapsed
                 );
 
-                let mut stdout = stdout();
-                if timer.log.is_logging && timer.oneliner {
-                    stdout.execute(MoveLeft(1000)).unwrap();
-                    stdout.execute(Clear(ClearType::FromCursorDown)).unwrap();
-                    stdout.write_all(name.to_string().as_bytes()).unwrap();
-                    stdout.write_all(progress.to_string().as_bytes()).unwrap();
-                    stdout.flush().unwrap();
-                } else if timer.log.is_logging {
-                    stdout.execute(MoveLeft(1000)).unwrap();
-                    stdout.execute(MoveUp(6)).unwrap();
-                    stdout.execute(Clear(ClearType::FromCursorDown)).unwrap();
-                    stdout.write_all("\n".as_bytes()).unwrap();
-                    stdout.write_all(name.to_string().as_bytes()).unwrap();
-                    stdout.write_all(output.to_string().as_bytes()).unwrap();
-                    stdout.flush().unwrap();
+                let mut stderr = stderr();
+                if timer.log.is_enabled(LogLevel::Info) && timer.log.plain_progress {
+                    let line = timer.format_plain(percent, seconds);
+                    stderr.write_all(line.as_bytes()).unwrap();
+                    stderr.flush().unwrap();
+                } else if timer.log.is_enabled(LogLevel::Info) && timer.oneliner {
+                    stderr.execute(MoveLeft(1000)).unwrap();
+                    stderr.execute(Clear(ClearType::FromCursorDown)).unwrap();
+                    let eta = format!(" ETA: {}", Self::format_eta(percent, seconds));
+                    stderr.write_all(name.to_string().as_bytes()).unwrap();
+                    stderr.write_all(progress.to_string().as_bytes()).unwrap();
+                    stderr.write_all(eta.as_bytes()).unwrap();
+                    stderr.flush().unwrap();
+                } else if timer.log.is_enabled(LogLevel::Info) {
+                    stderr.execute(MoveLeft(1000)).unwrap();
+                    stderr.execute(MoveUp(6)).unwrap();
+                    stderr.execute(Clear(ClearType::FromCursorDown)).unwrap();
+                    stderr.write_all("\n".as_bytes()).unwrap();
+                    stderr.write_all(name.to_string().as_bytes()).unwrap();
+                    stderr.write_all(output.to_string().as_bytes()).unwrap();
+                    stderr.flush().unwrap();
                 }
                 if count >= total || end != 0 {
-                    timer.log.println("\n".stylize());
+                    if !timer.log.plain_progress {
+                        timer.log.println("\n".stylize());
+                    }
                     break;
                 }
             }
         })
     }
 
+    /// Single line for logs e.g. 'Recovery Guesses [12.30%] 1.20M/10.0M guesses, 45.0K/sec, ETA 2 hours'
+    fn format_plain(&self, percent: f64, secs: u64) -> String {
+        let mut speed = format!("{}/sec", self.speed());
+        if !self.oneliner {
+            speed = format!("{} (GPU {}/sec)", speed, self.gpu_speed());
+        }
+        format!(
+            "{} [{:.2}%] {}/{} guesses, {}, ETA {}\n",
+            self.name,
+            percent,
+            Logger::format_num(self.count()),
+            Logger::format_num(self.total()),
+            speed,
+            Self::format_eta(percent, secs)
+        )
+    }
+
     fn format_eta(percent: f64, secs: u64) -> String {
         if percent == 100.0 {
             return "N/A".to_string();
@@ -228,12 +322,48 @@ impl Timer {
 impl Logger {
     /// Create logger that logs
     pub fn new() -> Self {
-        Self { is_logging: true }
+        Self {
+            is_logging: true,
+            level: LogLevel::Info,
+            status_interval: Duration::ZERO,
+            plain_progress: false,
+        }
     }
 
     /// Create logger that doesn't log
     pub fn off() -> Self {
-        Self { is_logging: false }
+        Self {
+            is_logging: false,
+            level: LogLevel::Info,
+            status_interval: Duration::ZERO,
+            plain_progress: false,
+        }
+    }
+
+    /// Copy of the logger that only prints output up to the `level`
+    pub fn with_level(&self, level: LogLevel) -> Self {
+        let mut copy = self.clone();
+        copy.level = level;
+        copy
+    }
+
+    /// Copy of the logger that only prints progress every `secs`
+    pub fn with_status_interval(&self, secs: u64) -> Self {
+        let mut copy = self.clone();
+        copy.status_interval = Duration::from_secs(secs);
+        copy
+    }
+
+    /// Copy of the logger that prints progress as appended lines for CI logs
+    pub fn with_plain_progress(&self, plain_progress: bool) -> Self {
+        let mut copy = self.clone();
+        copy.plain_progress = plain_progress;
+        copy
+    }
+
+    /// Seconds between progress updates (at least 1)
+    pub fn status_secs(&self) -> u64 {
+        max(self.status_interval.as_secs(), 1)
     }
 
     /// Create a new table logger, columns will be padded to heading length
@@ -285,30 +415,87 @@ impl Logger {
         )
     }
 
-    /// Print stylized text
+    /// Whether output at the `level` will be printed
+    pub fn is_enabled(&self, level: LogLevel) -> bool {
+        self.is_logging && level <= self.level
+    }
+
+    /// Print stylized text to stderr
     pub fn print(&self, output: StyledContent<&str>) {
-        let mut stdout = stdout();
-        if self.is_logging {
-            stdout.write_all(output.to_string().as_bytes()).unwrap();
-            stdout.flush().unwrap();
-        }
+        self.write(LogLevel::Info, output, "");
+    }
+
+    /// Print a question that waits for an answer even if info output is hidden
+    pub fn prompt(&self, output: StyledContent<&str>) {
+        self.write(LogLevel::Error, output, "");
     }
 
-    /// Print error text
+    /// Print debug text such as the hashcat command and why a mode was chosen (-v)
+    pub fn println_debug(&self, output: &str) {
+        let output = format!("[debug] {}", output);
+        self.write(LogLevel::Debug, output.as_str().dark_grey(), "\n");
+    }
+
+    /// Print trace text such as the raw hashcat output (-vv)
+    pub fn println_trace(&self, output: &str) {
+        let output = format!("[trace] {}", output);
+        self.write(LogLevel::Trace, output.as_str().dark_grey(), "\n");
+    }
+
+    /// Print warning text to stderr
+    pub fn println_warn(&self, output: &str) {
+        self.write(LogLevel::Warn, output.dark_yellow(), "\n");
+    }
+
+    /// Print error text to stderr
     pub fn println_err(&self, output: &str) {
         let mut split = output.split("\n");
-        self.print("\nError: ".dark_red().bold());
+        self.write(LogLevel::Error, "\nError: ".dark_red().bold(), "");
         while let Some(line) = split.next() {
-            self.println(line.stylize());
+            self.write(LogLevel::Error, line.stylize(), "\n");
+        }
+        self.write(LogLevel::Error, "\n If you found a bug please report it here: https://github.com/seed-cat/seedcat/issues".stylize(), "\n");
+    }
+
+    /// Print a one-line status that is cleared by `clear_status` once the work finishes
+    pub fn status(&self, output: &str) {
+        self.write(LogLevel::Info, output.dark_grey(), "");
+    }
+
+    /// Clear the line written by `status`
+    pub fn clear_status(&self) {
+        let mut stderr = stderr();
+        if self.is_enabled(LogLevel::Info) {
+            stderr.execute(MoveLeft(1000)).unwrap();
+            stderr.execute(Clear(ClearType::FromCursorDown)).unwrap();
         }
-        self.println("\n If you found a bug please report it here: https://github.com/seed-cat/seedcat/issues".stylize());
     }
 
-    /// Println stylized text
+    /// Println stylized text to stderr
     pub fn println(&self, output: StyledContent<&str>) {
+        self.write(LogLevel::Info, output, "\n");
+    }
+
+    fn write(&self, level: LogLevel, output: StyledContent<&str>, end: &str) {
+        let mut stderr = stderr();
+        if self.is_enabled(level) {
+            stderr.write_all(output.to_string().as_bytes()).unwrap();
+            stderr.write_all(end.as_bytes()).unwrap();
+            stderr.flush().unwrap();
+        }
+    }
+
+    /// Println the labelled result to stdout so it can be piped separately from the progress
+    pub fn result(&self, label: StyledContent<&str>, output: &str) {
         let mut stdout = stdout();
         if self.is_logging {
-            stdout.write_all(output.to_string().as_bytes()).unwrap();
+            // Only style the label when a user will see it
+            let label = match stdout.is_terminal() {
+                true => label.to_string(),
+                false => label.content().to_string(),
+            };
+            stdout.write_all(label.as_bytes()).unwrap();
+            stdout.write_all(output.as_bytes()).unwrap();
             stdout.write_all("\n".to_string().as_bytes()).unwrap();
             stdout.flush().unwrap();
         }
@@ -386,6 +573,30 @@ mod tests {
         assert_eq!(Logger::parse_num(" 123 ").unwrap(), 123);
     }
 
+    #[test]
+    fn parses_log_levels() {
+        assert_eq!(LogLevel::from_args(0, None).unwrap(), LogLevel::Info);
+        assert_eq!(LogLevel::from_args(1, None).unwrap(), LogLevel::Debug);
+        assert_eq!(LogLevel::from_args(3, None).unwrap(), LogLevel::Trace);
+        let warn = Some(" WARN ".to_string());
+        assert_eq!(
+            LogLevel::from_args(0, warn.clone()).unwrap(),
+            LogLevel::Warn
+        );
+        assert_eq!(LogLevel::from_args(1, warn).unwrap(), LogLevel::Debug);
+        assert!(LogLevel::from_args(0, Some("loud".to_string())).is_err());
+        let directives = Some("hyper=trace, error".to_string());
+        assert_eq!(LogLevel::from_args(0, directives).unwrap(), LogLevel::Error);
+        let modules = Some("hyper=warn".to_string());
+        assert_eq!(LogLevel::from_args(0, modules).unwrap(), LogLevel::Info);
+
+        let log = Logger::new().with_level(LogLevel::Debug);
+        assert!(log.is_enabled(LogLevel::Error));
+        assert!(log.is_enabled(LogLevel::Debug));
+        assert!(!log.is_enabled(LogLevel::Trace));
+        assert!(!Logger::off().is_enabled(LogLevel::Error));
+    }
+
     #[test]
     fn formats_tables() {
         let logger = Logger::new();
@@ -394,6 +605,22 @@ mod tests {
         assert_eq!(formatted, "1   |2|3  ");
     }
 
+    #[tokio::test]
+    async fn throttles_status_updates() {
+        let log = Logger::off().with_status_interval(60);
+        assert_eq!(log.status_secs(), 60);
+        assert_eq!(Logger::off().status_secs(), 1);
+
+        // still finishes immediately once the total is reached
+        let timer = log.time("", 100).await;
+        let handle = timer.start().await;
+        timer.add(50);
+        sleep(Duration::from_millis(200)).await;
+        timer.add(50);
+        handle.await.unwrap();
+        assert_eq!(timer.count(), 100);
+    }
+
     #[tokio::test]
     async fn timer_starts_and_ends() {
         let timer = Logger::off().time("", 100).await;
@@ -422,6 +649,23 @@ mod tests {
         assert_eq!(Logger::format_num(56_700_000_000_000), "56.7T");
     }
 
+    #[tokio::test]
+    async fn formats_plain_progress() {
+        let log = Logger::off().with_plain_progress(true);
+        let timer = log.time_verbose("Recovery Guesses", 10_000_000, 2).await;
+        timer.store(600_000);
+        assert_eq!(
+            timer.format_plain(12.0, 1),
+            "Recovery Guesses [12.00%] 1.20M/10.0M guesses, 1.20M/sec (GPU 600K/sec), ETA 7 secs\n"
+        );
+        let timer = log.time("Writing Hashes", 100).await;
+        timer.add(50);
+        assert_eq!(
+            timer.format_plain(50.0, 1),
+            "Writing Hashes [50.00%] 50/100 guesses, 50/sec, ETA 1 secs\n"
+        );
+    }
+
     #[test]
     fn formats_eta() {
         assert_eq!(Timer::format_eta(50.0, 60), "1 mins, 0 secs");
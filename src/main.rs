@@ -1,34 +1,86 @@
-use std::io::{BufRead, Write};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufWriter, IsTerminal, Write};
 use std::net::{SocketAddr, TcpStream};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::str::FromStr;
+use std::thread::JoinHandle;
 use std::time::Duration;
-use std::{env, io};
+use std::{env, io, thread};
 
-use anyhow::{bail, Result};
-use clap::{Args, Parser, Subcommand};
+use anyhow::{bail, format_err, Error, Result};
+use clap::{ArgGroup, Args, Parser, Subcommand};
 use crossterm::style::Stylize;
 
 use crate::address::AddressValid;
 use crate::benchmarks::run_benchmarks;
+use crate::config::{expand_args_stdin, expand_config, without_args_stdin};
 use crate::hashcat::{Hashcat, HashcatExe, HashcatRunner};
-use crate::logger::Logger;
-use crate::passphrase::Passphrase;
-use crate::seed::{Finished, Seed};
+use crate::logger::{Attempt, LogLevel, Logger, Timer};
+use crate::monero::MoneroSeed;
+use crate::passphrase::{Passphrase, DEFAULT_MAX_DICT_MB, ERR_MSG as PASSPHRASE_SYNTAX};
+use crate::seed::{
+    bip39_wordlist, load_wordlist, Finished, Seed, Wordlist, ERR_MSG as SEED_SYNTAX,
+};
 
 mod address;
+mod backend;
 mod benchmarks;
 mod combination;
+mod config;
 mod hashcat;
 mod logger;
+mod monero;
 mod passphrase;
 mod permutations;
 mod seed;
 mod tests;
 
 const HASHCAT_PATH: &str = "hashcat";
+const HASHCAT_ENV: &str = "SEEDCAT_HASHCAT";
 const SEPARATOR: &str = ",";
+const DEFAULT_COMPRESSION: u32 = 1;
+// The hashcat module for BIP39 seeds
+const DEFAULT_HASH_MODE: u32 = 28510;
+const MAX_BATCHES_LOGGED: usize = 10;
+const DUMP_VALID_MAX: usize = 1_000_000;
+const XPUB_PREVIEW_ADDRESSES: u32 = 3;
+// Roughly what a high-end gaming GPU can guess, used to spot jobs that will never finish
+const ESTIMATED_GUESSES_PER_SEC: u64 = 1_000_000;
+const INFEASIBLE_SECS: u64 = 365 * 24 * 60 * 60;
+const INTERNET_HOSTS: [&str; 3] = ["1.1.1.1:53", "8.8.8.8:53", "209.85.233.101:80"];
+// Exit codes of a recovery that scripts can branch on
+const EXIT_FOUND: i32 = 0;
+const EXIT_CONFIG_ERROR: i32 = 1;
+const EXIT_NOT_FOUND: i32 = 2;
+const EXIT_RUNTIME_ERROR: i32 = 3;
+const EXIT_ABORTED: i32 = 4;
+const EXIT_STOPPED: i32 = 5;
+/// Complete commands for common recoveries printed by `seedcat examples`
+const EXAMPLES: [(&str, &str); 5] = [
+    (
+        "Missing the last word",
+        "seedcat --address \"1AtD3g5AmR4fMsCRa1haNGmvCTVWq7YfzD\" \\\n --seed \"toy donkey chaos ethics vapor struggle ramp dune join nothing wait ?\"",
+    ),
+    (
+        "Unknown passphrase of 'secret' followed by 3 digits",
+        "seedcat --address \"1Aa7DosYfoYJwZDmMPPTqtH7dXUehYbyMu\" \\\n --seed \"toy donkey chaos ethics vapor struggle ramp dune join nothing wait length\" \\\n --passphrase \"secret?d?d?d\"",
+    ),
+    (
+        "Unknown word order (the first 3 words are in place)",
+        "seedcat --address \"1AtD3g5AmR4fMsCRa1haNGmvCTVWq7YfzD\" \\\n --combinations 12 --seed \"^toy ^donkey ^chaos zoo vapor struggle zone nothing join ethics ramp wait length dune\"",
+    ),
+    (
+        "Unknown passphrase for a master XPUB",
+        "seedcat --address \"xpub661MyMwAqRbcEZjJh7cPj6aGJ9NpRDUfpNz65bLKQQKR6dznUoszbxGyF7JUeCCNdYyboeD9EnRGgz8UfZW2hMzMBXA7SLumhtMU8VWy65L\" \\\n --seed \"dad moral begin apology cheap vast clerk limb shaft salt citizen awesome\" \\\n --passphrase \"?l?d?d?d?d?d?d\"",
+    ),
+    (
+        "Unsure of the derivation path",
+        "seedcat --address \"1NgqeNE2EfBthz4enLb7vs1bapDEQbbivT\" \\\n --seed \"toy donkey chaos ethics vapor struggle ramp dune join nothing wait ?\" \\\n --derivation \"m/0/?4 m/44h/0h/0h/0/?4\"",
+    ),
+];
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None, arg_required_else_help = true, args_conflicts_with_subcommands = true)]
@@ -44,6 +96,110 @@ pub struct Cli {
 pub enum CliCommand {
     /// Runs benchmarks and tests of the application
     Test(BenchOption),
+    /// Lists Monero seeds with valid checksums (for use with a Monero-specific cracker)
+    Monero(MoneroOption),
+    /// Lists every last word that completes a seed with a valid checksum
+    Lastword(LastwordOption),
+    /// Measures the raw BIP39 hashrate of your devices using the hashcat benchmark
+    Speed,
+    /// Counts the seeds with valid checksums without needing an address or hashcat
+    Count(CountOption),
+    /// Prints every passphrase candidate to stdout using hashcat's --stdout mode
+    Passphrases(PassphrasesOption),
+    /// Prints example commands for common recoveries
+    Examples,
+}
+
+#[derive(Args, Debug)]
+pub struct MoneroOption {
+    /// Monero seed words with wildcards e.g. 'cafe,?,wou?,...' (13 or 25 words)
+    #[arg(short, long, value_name = "word word...")]
+    seed: String,
+}
+
+#[derive(Args, Debug)]
+pub struct LastwordOption {
+    /// Every seed word except the last e.g. 'cage,zoo,...' (11, 14, 17, 20 or 23 words)
+    #[arg(short, long, value_name = "word word...")]
+    seed: String,
+
+    /// File of 2048 words (one per line) to use instead of the English BIP39 words
+    #[arg(long, value_name = "file")]
+    wordlist: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct CountOption {
+    /// Seed words with wildcards e.g. 'cage,?,zo?,?be,?oo?,toward|st?,able...'
+    #[arg(short, long, value_name = "word word...")]
+    seed: String,
+
+    /// Guess all permutations of a # of seed words
+    #[arg(short, long, value_name = "# words")]
+    combinations: Option<usize>,
+
+    /// Seed words were written across a grid but belong in column order e.g. 'column:4,3'
+    #[arg(long, value_name = "column:ROWS,COLUMNS")]
+    order: Option<String>,
+
+    /// Also guess two word positions swapped with each other e.g. '3,7'
+    #[arg(
+        long,
+        value_name = "position,position",
+        conflicts_with = "combinations"
+    )]
+    swap: Option<String>,
+
+    /// File of 2048 words (one per line) to use instead of the English BIP39 words
+    #[arg(long, value_name = "file")]
+    wordlist: Option<String>,
+
+    /// Lists every valid seed (only for under 100K seeds)
+    #[arg(short, long, default_value_t = false)]
+    list: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PassphrasesOption {
+    /// Dictionaries and/or mask e.g. './dict.txt' '?l?l?l?d?1'
+    #[arg(short, long, value_name = "MASK|DICT", required = true)]
+    passphrase: Vec<String>,
+
+    /// User defined charset for use in passphrase mask attack
+    #[arg(short = '1', long, value_name = "chars")]
+    custom_charset1: Option<String>,
+
+    /// User defined charset for use in passphrase mask attack
+    #[arg(short = '2', long, value_name = "chars")]
+    custom_charset2: Option<String>,
+
+    /// User defined charset for use in passphrase mask attack
+    #[arg(short = '3', long, value_name = "chars")]
+    custom_charset3: Option<String>,
+
+    /// User defined charset for use in passphrase mask attack
+    #[arg(short = '4', long, value_name = "chars")]
+    custom_charset4: Option<String>,
+
+    /// Passphrase dictionaries, masks and custom charsets are hex-encoded bytes
+    #[arg(long, default_value_t = false)]
+    passphrase_hex: bool,
+
+    /// Also tries dictionary entries without accents e.g. 'café' as 'cafe'
+    #[arg(long, default_value_t = false, conflicts_with = "passphrase_hex")]
+    fold_accents: bool,
+
+    /// Known start of the passphrase added before every candidate e.g. 'satoshi'
+    #[arg(long, value_name = "text")]
+    passphrase_prefix: Option<String>,
+
+    /// Known end of the passphrase added after every candidate e.g. '2009'
+    #[arg(long, value_name = "text")]
+    passphrase_suffix: Option<String>,
+
+    /// Largest dictionary file in MB that will be loaded into memory
+    #[arg(long, value_name = "MB", default_value_t = DEFAULT_MAX_DICT_MB)]
+    max_dict_mb: u64,
 }
 
 #[derive(Args, Debug)]
@@ -70,28 +226,154 @@ pub struct BenchOption {
     diff: Option<String>,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
+#[command(group(ArgGroup::new("passphrase_attack").args(["passphrase", "passphrase_common"])))]
 pub struct CliRun {
     /// Address e.g. 'bc1q490...' OR master xpub key e.g. 'xpub661MyMwAqRbc...'
-    #[arg(short, long, value_name = "address")]
-    address: String,
+    #[arg(short, long, value_name = "address", required_unless_present_any = ["hash160", "script_pubkey"])]
+    address: Option<String>,
+
+    /// Raw HASH160 of the address in hex instead of the address (requires --address-type)
+    #[arg(long, value_name = "hex", requires = "address_type", conflicts_with_all = ["address", "script_pubkey"])]
+    hash160: Option<String>,
+
+    /// Raw scriptPubKey in hex instead of the address (type is inferred from the script)
+    #[arg(long = "scriptpubkey", value_name = "hex", conflicts_with = "address")]
+    script_pubkey: Option<String>,
+
+    /// Address type of the --hash160 e.g. 'P2PKH', 'P2SH-P2WPKH', 'P2WPKH' or 'any' to try every type
+    #[arg(long, value_name = "type")]
+    address_type: Option<String>,
 
     /// Seed words with wildcards e.g. 'cage,?,zo?,?be,?oo?,toward|st?,able...'
-    #[arg(short, long, value_name = "word word...")]
-    seed: String,
+    #[arg(
+        short,
+        long,
+        value_name = "word word...",
+        required_unless_present = "seed_list"
+    )]
+    seed: Option<String>,
+
+    /// File with one complete seed per line to test instead of --seed e.g. './candidates.txt'
+    #[arg(
+        long,
+        value_name = "file",
+        conflicts_with_all = ["seed", "order", "swap", "combinations", "any_length"]
+    )]
+    seed_list: Option<String>,
+
+    /// Seed words were written across a grid but belong in column order e.g. 'column:4,3'
+    #[arg(long, value_name = "column:ROWS,COLUMNS")]
+    order: Option<String>,
+
+    /// Also guess two word positions swapped with each other e.g. '3,7'
+    #[arg(long, value_name = "position,position", conflicts_with_all = ["combinations", "any_length"])]
+    swap: Option<String>,
+
+    /// Also guesses seeds with invalid checksums (only for wallets that never checked them)
+    #[arg(long, default_value_t = false)]
+    skip_checksum: bool,
 
     /// Derivation paths with wildcards e.g. 'm/0/0,m/49h/0h/0h/?2/?10'
+    /// (paths can be separated by ',' '|' or spaces)
     #[arg(short, long, value_name = "path path...")]
     derivation: Option<String>,
 
+    /// Standard derivation of a BIP44, BIP49 or BIP84 wallet instead of writing the --derivation
+    #[arg(long, value_name = "44|49|84", conflicts_with = "derivation")]
+    bip: Option<u32>,
+
+    /// Account number of the --bip derivation e.g. 1 for the second account
+    #[arg(long, value_name = "#", default_value_t = 0, requires = "bip")]
+    account: u32,
+
+    /// Address index of the --bip derivation e.g. 4 for the fifth receive address
+    #[arg(long, value_name = "#", default_value_t = 0, requires = "bip")]
+    index: u32,
+
+    /// Also checks the change address of each path e.g. 'm/84h/0h/0h/1/0' for 'm/84h/0h/0h/0/0'
+    #[arg(long, default_value_t = false)]
+    include_change: bool,
+
+    /// Checks every address index in a range e.g. '0-100' instead of the last node of each path
+    #[arg(long, value_name = "start-end")]
+    address_range: Option<String>,
+
     /// Dictionaries and/or mask e.g. './dict.txt' '?l?l?l?d?1'
     #[arg(short, long, value_name = "MASK|DICT")]
     passphrase: Option<Vec<String>>,
 
+    /// Tries common passphrase patterns (words, words with digits, PINs) until one succeeds
+    #[arg(long, default_value_t = false, conflicts_with = "passphrase")]
+    passphrase_common: bool,
+
+    /// Passphrase dictionaries, masks and custom charsets are hex-encoded bytes
+    #[arg(long, default_value_t = false, requires = "passphrase")]
+    passphrase_hex: bool,
+
+    /// Guesses control bytes (0x00-0x1F) in masks without warning e.g. from '?b'
+    #[arg(long, default_value_t = false)]
+    allow_control_bytes: bool,
+
+    /// Also tries dictionary entries without accents e.g. 'café' as 'cafe'
+    #[arg(long, default_value_t = false, conflicts_with = "passphrase_hex")]
+    fold_accents: bool,
+
+    /// Known start of the passphrase added before every candidate e.g. 'satoshi'
+    #[arg(long, value_name = "text", requires = "passphrase_attack")]
+    passphrase_prefix: Option<String>,
+
+    /// Known end of the passphrase added after every candidate e.g. '2009'
+    #[arg(long, value_name = "text", requires = "passphrase_attack")]
+    passphrase_suffix: Option<String>,
+
+    /// Hashcat Markov stats file (.hcstat2) used to try likely mask candidates first
+    #[arg(long, value_name = "file", requires = "passphrase_attack")]
+    markov_hcstat2: Option<String>,
+
+    /// Passphrase attack to try if the previous attacks are exhausted (repeatable)
+    #[arg(long, value_name = "MASK|DICT", requires = "passphrase")]
+    then_passphrase: Vec<String>,
+
+    /// The seed is already known (e.g. from a previous run) so only guess the passphrase
+    #[arg(
+        long,
+        default_value_t = false,
+        requires = "passphrase_attack",
+        conflicts_with = "combinations"
+    )]
+    passphrase_only: bool,
+
     /// Guess all permutations of a # of seed words
     #[arg(short, long, value_name = "# words")]
     combinations: Option<usize>,
 
+    /// Guess permutations of every seed length the words could fill (shortest search first)
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["combinations", "passphrase_only", "stdout_candidates", "preview"]
+    )]
+    any_length: bool,
+
+    /// Split the seeds into N near equal shards so separate machines can each guess one
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "shard_index",
+        conflicts_with_all = ["closest_first", "any_length"],
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    shards: Option<u32>,
+
+    /// Which of the --shards this machine guesses, counting from 0
+    #[arg(long, value_name = "I", requires = "shards")]
+    shard_index: Option<u32>,
+
+    /// With --combinations try the word orders closest to the given order first
+    #[arg(long, default_value_t = false, requires = "combinations")]
+    closest_first: bool,
+
     /// User defined charset for use in passphrase mask attack
     #[arg(short = '1', long, value_name = "chars")]
     custom_charset1: Option<String>,
@@ -108,10 +390,104 @@ pub struct CliRun {
     #[arg(short = '4', long, value_name = "chars")]
     custom_charset4: Option<String>,
 
+    /// Always runs hashcat with -S (slow candidates) which is usually faster for <100M passphrases
+    #[arg(long, default_value_t = false, conflicts_with = "no_single_mode")]
+    single_mode: bool,
+
+    /// Never runs hashcat with -S (slow candidates) in case it is slower on your GPU
+    #[arg(long, default_value_t = false)]
+    no_single_mode: bool,
+
+    /// Largest dictionary file in MB that will be loaded into memory
+    #[arg(long, value_name = "MB", default_value_t = DEFAULT_MAX_DICT_MB)]
+    max_dict_mb: u64,
+
+    /// Hashcat session name for restore files (defaults to a unique name per run)
+    #[arg(long, value_name = "name")]
+    session: Option<String>,
+
+    /// Sends seeds in the same order every run so hashcat '--skip' and '--restore' resume correctly
+    /// (slower in stdin mode, enabled by default when passing those hashcat options)
+    #[arg(long, default_value_t = false)]
+    ordered: bool,
+
+    /// In stdin mode writes every candidate to temp files before running hashcat instead of piping
+    /// them (uses more disk space and delays starting hashcat but avoids stalled pipes)
+    #[arg(long, default_value_t = false, conflicts_with = "stdout_candidates")]
+    candidate_files: bool,
+
+    /// Gzip level 0-9 of the temp files hashcat reads (lower levels start hashcat faster)
+    #[arg(long, value_name = "level", default_value_t = DEFAULT_COMPRESSION, value_parser = clap::value_parser!(u32).range(0..=9))]
+    compression: u32,
+
+    /// Hashcat '-m' module number, only change this if your hashcat fork uses a different number
+    #[arg(long, value_name = "num", default_value_t = DEFAULT_HASH_MODE)]
+    hashcat_mode: u32,
+
+    /// Write candidates to a file or named pipe ('-' for stdout) instead of running hashcat
+    #[arg(long, value_name = "path")]
+    stdout_candidates: Option<String>,
+
+    /// Prints the first N seed and passphrase candidates then exits without running hashcat
+    #[arg(long, value_name = "N")]
+    preview: Option<usize>,
+
+    /// Writes every valid seed permutation to a file to try by hand instead of running hashcat
+    #[arg(long, value_name = "file", requires = "combinations")]
+    dump_valid: Option<String>,
+
+    /// Loads options from a TOML file e.g. 'recovery.toml' (command line options take precedence)
+    #[arg(long, value_name = "file")]
+    config: Option<String>,
+
+    /// Reads the options as a JSON object from stdin e.g. '{"seed": "zoo ?"}' (for GUI frontends)
+    #[arg(long, default_value_t = false)]
+    args_stdin: bool,
+
+    /// On success prints the master XPRV and the private key (WIF) of the address
+    #[arg(long, default_value_t = false)]
+    export_wallet: bool,
+
+    /// Prints debug output such as the exact hashcat command being run (-vv also prints hashcat output)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Seconds between progress updates [default: 1, or 10 when not printing to a terminal]
+    #[arg(long, value_name = "secs", value_parser = clap::value_parser!(u64).range(1..))]
+    status_interval: Option<u64>,
+
+    /// Keeps searching after the first match and reports every seed, passphrase and derivation found
+    #[arg(long, default_value_t = false)]
+    find_all: bool,
+
+    /// Prints progress as a single appended line per update without cursor movement (for CI logs)
+    #[arg(long, default_value_t = false)]
+    plain_progress: bool,
+
+    /// Only prints the final result and any errors, skipping the prompt
+    #[arg(short, long, default_value_t = false, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Keeps the temp files if interrupted with Ctrl-C (always kept when using --session)
+    #[arg(long, default_value_t = false)]
+    keep_files: bool,
+
     /// Skips the prompt and starts immediately
     #[arg(short = 'y', long, default_value_t = false)]
     skip_prompt: bool,
 
+    /// Prints the first addresses derived from an XPUB to check it matches your wallet
+    #[arg(long, default_value_t = false)]
+    xpub_preview: bool,
+
+    /// Skips checking whether the internet is connected
+    #[arg(long, default_value_t = false)]
+    no_internet_check: bool,
+
+    /// Milliseconds to wait for each host when checking the internet connection
+    #[arg(long, value_name = "millis", default_value_t = 100)]
+    internet_timeout: u64,
+
     /// Pass options directly to hashcat (https://hashcat.net/wiki/doku.php?id=hashcat)
     #[arg(last = true, value_name = "hashcat options")]
     hashcat: Vec<String>,
@@ -121,7 +497,26 @@ pub struct CliRun {
 async fn main() {
     let log = Logger::new();
 
-    let cli: Cli = Cli::parse();
+    let args = expand_args_stdin(env::args().collect(), io::stdin());
+    let args = match args.and_then(expand_config) {
+        Ok(args) => args,
+        Err(err) => {
+            log.println_err(&err.to_string());
+            exit(EXIT_CONFIG_ERROR);
+        }
+    };
+    let resume_args = without_args_stdin(&args);
+    // clap exits with 2 on usage errors which would clash with EXIT_NOT_FOUND
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            match err.use_stderr() {
+                true => exit(EXIT_CONFIG_ERROR),
+                false => exit(0),
+            }
+        }
+    };
     if let Some(CliCommand::Test(option)) = cli.cmd {
         if let Err(err) = run_benchmarks(option).await {
             log.println_err(&err.to_string());
@@ -130,17 +525,414 @@ async fn main() {
         exit(0);
     }
 
+    if let Some(CliCommand::Monero(option)) = cli.cmd {
+        if let Err(err) = run_monero(&option, &log) {
+            log.println_err(&err.to_string());
+            exit(1);
+        }
+        exit(0);
+    }
+
+    if let Some(CliCommand::Lastword(option)) = cli.cmd {
+        let words = wordlist_arg(&option.wordlist)
+            .and_then(|wordlist| Seed::checksum_words(&option.seed, &wordlist));
+        match words {
+            Ok(words) => words.iter().for_each(|word| println!("{}", word)),
+            Err(err) => {
+                log.println_err(&err.to_string());
+                exit(1);
+            }
+        }
+        exit(0);
+    }
+
+    if let Some(CliCommand::Count(option)) = cli.cmd {
+        if let Err(err) = run_count(&option, &log) {
+            log.println_err(&err.to_string());
+            exit(1);
+        }
+        exit(0);
+    }
+
+    if let Some(CliCommand::Passphrases(option)) = cli.cmd {
+        if let Err(err) = run_passphrases(&option, &log).await {
+            log.println_err(&err.to_string());
+            exit(1);
+        }
+        exit(0);
+    }
+
+    if let Some(CliCommand::Examples) = cli.cmd {
+        run_examples(&log);
+        exit(0);
+    }
+
+    if let Some(CliCommand::Speed) = cli.cmd {
+        if let Err(err) = run_speed(&log) {
+            log.println_err(&err.to_string());
+            exit(1);
+        }
+        exit(0);
+    }
+
     if let Some(run) = cli.run {
-        let mut hashcat = match configure(&run, &log) {
-            Ok(hashcat) => hashcat,
-            Err(err) => return log.println_err(&err.to_string()),
+        let interval = match io::stderr().is_terminal() {
+            true => run.status_interval.unwrap_or(1),
+            false => run.status_interval.unwrap_or(10),
+        };
+        let level = match LogLevel::from_args(run.verbose, env::var("RUST_LOG").ok()) {
+            Ok(level) => level,
+            Err(err) => {
+                log.println_warn(&format!("{}, defaulting to info", err));
+                LogLevel::Info
+            }
         };
-        let (_, finished) = match hashcat.run(&log, false).await {
-            Ok(finished) => finished,
-            Err(err) => return log.println_err(&err.to_string()),
+        let log = log
+            .with_level(level)
+            .with_status_interval(interval)
+            .with_plain_progress(run.plain_progress);
+        if let Some(path) = &run.stdout_candidates {
+            if let Err(err) = run_candidates(&run, path, &log).await {
+                log.println_err(&err.to_string());
+                match err.is::<RuntimeError>() {
+                    true => exit(EXIT_RUNTIME_ERROR),
+                    false => exit(EXIT_CONFIG_ERROR),
+                }
+            }
+            exit(0);
+        }
+        if let Some(path) = &run.dump_valid {
+            if let Err(err) = run_dump_valid(&run, path, &log) {
+                log.println_err(&err.to_string());
+                exit(EXIT_CONFIG_ERROR);
+            }
+            exit(0);
+        }
+        if let Some(num) = run.preview {
+            if let Err(err) = run_preview(&run, num, &log) {
+                log.println_err(&err.to_string());
+                exit(EXIT_CONFIG_ERROR);
+            }
+            exit(0);
+        }
+        let progress = match run.quiet {
+            true => Logger::off(),
+            false => log.clone(),
+        };
+        let (hashcat, finished) = if run.any_length {
+            match run_any_length(&run, &progress, &log).await {
+                Ok(result) => result,
+                Err(err) => {
+                    log.println_err(&err.to_string());
+                    match err.is::<RuntimeError>() {
+                        true => exit(EXIT_RUNTIME_ERROR),
+                        false => exit(EXIT_CONFIG_ERROR),
+                    }
+                }
+            }
+        } else {
+            let mut hashcat = match configure(&run, &progress) {
+                Ok(hashcat) => hashcat,
+                Err(err) => {
+                    log.println_err(&err.to_string());
+                    exit(EXIT_CONFIG_ERROR);
+                }
+            };
+            // chained attacks restart from the first so a single '--skip' cannot resume them
+            if run.then_passphrase.is_empty() && !run.passphrase_common {
+                hashcat.set_resume_args(resume_args);
+            }
+            hashcat.spawn_interrupt_handler(keep_files(&run), &log);
+            match hashcat.run(&progress, false).await {
+                Ok((_, finished)) => (hashcat, finished),
+                Err(err) => {
+                    log.println_err(&err.to_string());
+                    exit(EXIT_RUNTIME_ERROR);
+                }
+            }
         };
         log_finished(&finished, &log);
+        if finished.stopped.is_some() {
+            Hashcat::log_resume(&log, hashcat.resume_command());
+        }
+        if run.export_wallet {
+            if let Err(err) = log_wallet(&finished, hashcat.found_address(&finished), &log) {
+                log.println_err(&err.to_string());
+                exit(EXIT_RUNTIME_ERROR);
+            }
+        }
+        match finished {
+            Finished {
+                seed: Some(_),
+                unverified: true,
+                ..
+            } => exit(EXIT_RUNTIME_ERROR),
+            Finished { seed: Some(_), .. } => exit(EXIT_FOUND),
+            Finished {
+                stopped: Some(_), ..
+            } => exit(EXIT_STOPPED),
+            _ => exit(EXIT_NOT_FOUND),
+        }
+    }
+}
+
+/// Error from running hashcat rather than from the options, exiting with EXIT_RUNTIME_ERROR
+#[derive(Debug)]
+struct RuntimeError(Error);
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+/// Prints all the Monero seeds that have a valid checksum word
+fn run_monero(option: &MoneroOption, log: &Logger) -> Result<()> {
+    let mut seed = MoneroSeed::from_arg(&option.seed)?;
+    log.heading("Seedcat Monero Checksums");
+    log.format_attempt("Seeds", &seed);
+
+    let mut valid = 0;
+    while let Some(next) = seed.next_valid() {
+        println!("{}", next);
+        valid += 1;
+    }
+    log.print_num("\nValid Seeds: ", valid);
+    Ok(())
+}
+
+/// Prints how many seeds have a valid checksum to help tune wildcards before running
+fn run_count(option: &CountOption, log: &Logger) -> Result<()> {
+    let seed = seed_arg(&option.seed, &option.order)?;
+    let wordlist = wordlist_arg(&option.wordlist)?;
+    let mut seed = Seed::from_wordlist(&seed, &option.combinations, &wordlist)?;
+    if let Some(swap) = &option.swap {
+        seed = seed.with_swap(swap)?;
+    }
+    seed.validate_length()?;
+    log.heading("Seedcat Seed Count");
+    log.format_attempt("Seeds", &seed);
+
+    let label = match seed.is_exact_count() {
+        true => "Valid Seeds: ",
+        false => "Valid Seeds (estimated): ",
+    };
+    let valid = seed.valid_seeds();
+    log.result(label.dark_green().bold(), &valid.to_string());
+    if valid == 0 {
+        log.println_warn("No seeds have a valid checksum, one of your known words is likely wrong");
     }
+    if option.list {
+        if !seed.is_exact_count() {
+            bail!("Too many seeds to list, try using fewer wildcards");
+        }
+        for seed in seed.preview(usize::MAX) {
+            println!("{}", seed);
+        }
+    }
+    Ok(())
+}
+
+/// Prints every passphrase so users can check what their args expand to
+async fn run_passphrases(option: &PassphrasesOption, log: &Logger) -> Result<()> {
+    let exe = validate_exe()?;
+    let charsets = vec![
+        option.custom_charset1.clone(),
+        option.custom_charset2.clone(),
+        option.custom_charset3.clone(),
+        option.custom_charset4.clone(),
+    ];
+    Passphrase::validate_dict_sizes(&option.passphrase, option.max_dict_mb)?;
+    let mut passphrase = match option.passphrase_hex {
+        true => Passphrase::from_hex_arg(&option.passphrase, &charsets)?,
+        false => Passphrase::from_arg(&option.passphrase, &charsets)?,
+    };
+    if option.fold_accents {
+        passphrase = passphrase.with_fold_accents()?;
+    }
+    if option.passphrase_prefix.is_some() || option.passphrase_suffix.is_some() {
+        let prefix = option.passphrase_prefix.clone().unwrap_or_default();
+        let suffix = option.passphrase_suffix.clone().unwrap_or_default();
+        passphrase = passphrase.with_affixes(&prefix, &suffix)?;
+    }
+    log.heading("Seedcat Passphrases");
+    log.format_attempt("Passphrases", &passphrase);
+    exe.print_passphrases(&passphrase, log).await
+}
+
+/// Prints runnable commands along with the seed and passphrase syntax
+fn run_examples(log: &Logger) {
+    log.heading("Seedcat Examples");
+    for (name, command) in EXAMPLES {
+        log.println(format!("{}:", name).as_str().bold());
+        log.println(format!("{}\n", command).as_str().stylize());
+    }
+    log.println(SEED_SYNTAX.trim().stylize());
+    log.println(PASSPHRASE_SYNTAX.trim_end().stylize());
+}
+
+/// Prints the hashes per second the devices can guess without running a recovery
+fn run_speed(log: &Logger) -> Result<()> {
+    let exe = validate_exe()?;
+    log.heading("Seedcat Speed");
+    log_devices(&exe, log);
+    log.println("Running hashcat benchmark please be patient...".bold());
+    let speed = exe.benchmark()?;
+    let output = format!("{}/sec", Logger::format_num(speed));
+    log.result("BIP39 Speed: ".dark_green().bold(), &output);
+    Ok(())
+}
+
+/// Writes the candidates that would be piped into hashcat so other tools can consume them
+async fn run_candidates(cli: &CliRun, path: &str, log: &Logger) -> Result<()> {
+    let log = match cli.quiet {
+        true => Logger::off(),
+        false => log.clone(),
+    };
+    let out: Box<dyn Write + Send + Sync> = match path {
+        "-" => Box::new(io::stdout()),
+        path => {
+            let file =
+                File::create(path).map_err(|_| format_err!("Unable to create file '{}'", path))?;
+            Box::new(file)
+        }
+    };
+    let mut hashcat = configure(cli, &log)?;
+    hashcat.spawn_interrupt_handler(keep_files(cli), &log);
+    let candidates = hashcat.write_candidates(out, &log).await;
+    candidates.map_err(|err| Error::new(RuntimeError(err)))
+}
+
+/// Prints the first candidates so users can check their seed and passphrase args expand as intended
+fn run_preview(cli: &CliRun, num: usize, log: &Logger) -> Result<()> {
+    let log = match cli.quiet {
+        true => Logger::off(),
+        false => log.clone(),
+    };
+    let hashcat = configure(cli, &log)?;
+    for candidate in hashcat.preview(num)? {
+        println!("{}", candidate);
+    }
+    Ok(())
+}
+
+/// Runs '--combinations' for each seed length the words could fill, stopping once one is found
+async fn run_any_length(
+    cli: &CliRun,
+    progress: &Logger,
+    log: &Logger,
+) -> Result<(Hashcat, Finished)> {
+    let seed = seed_arg(cli.seed.as_deref().unwrap_or_default(), &cli.order)?;
+    progress.heading("Seedcat Seed Lengths");
+    let mut lengths = vec![];
+    for len in Seed::pool_lengths(&seed)? {
+        match Seed::from_args(&seed, &Some(len)) {
+            Ok(seeds) => {
+                progress.print_num(&format!("{} Words: ", len), seeds.total());
+                lengths.push((seeds.total(), len));
+            }
+            Err(err) => {
+                let err = err.to_string();
+                let reason = err.lines().next().unwrap_or_default();
+                progress.println_warn(&format!("{} Words: Skipped ({})", len, reason));
+            }
+        }
+    }
+    if lengths.is_empty() {
+        bail!("Every seed length is infeasible to guess, try anchoring words in their correct position with '^'");
+    }
+    // the cheapest lengths finish first so a match is found as soon as possible
+    lengths.sort();
+
+    let mut cli = cli.clone();
+    cli.any_length = false;
+    let mut result = None;
+    for (index, (_, len)) in lengths.into_iter().enumerate() {
+        if result.is_some() {
+            let output = format!("No seed found, trying {} words next\n", len);
+            progress.println(output.as_str().dark_yellow());
+        }
+        cli.combinations = Some(len);
+        let mut hashcat = configure(&cli, progress)?;
+        if index == 0 {
+            hashcat.spawn_interrupt_handler(keep_files(&cli), log);
+        }
+        // the first prompt covers every length shown above
+        cli.skip_prompt = true;
+        let (_, finished) = hashcat
+            .run(progress, false)
+            .await
+            .map_err(|err| Error::new(RuntimeError(err)))?;
+        let found = finished.seed.is_some();
+        result = Some((hashcat, finished));
+        if found {
+            break;
+        }
+    }
+    result.ok_or_else(|| format_err!("No seed lengths to guess"))
+}
+
+/// Writes the valid seed permutations so users can try them in their wallet by hand
+fn run_dump_valid(cli: &CliRun, path: &str, log: &Logger) -> Result<()> {
+    let mut seed = seed_from_cli(cli)?;
+    if cli.skip_checksum {
+        seed = seed.with_skip_checksum();
+    }
+    if cli.closest_first {
+        seed = seed.with_closest_first();
+    }
+    seed.validate_length()?;
+    seed.validate_checksums()?;
+    log.heading("Seedcat Valid Seeds");
+    log.format_attempt("Seeds", &seed);
+
+    let valid = seed.valid_seeds();
+    if valid > DUMP_VALID_MAX as u64 {
+        log.println_warn(&format!(
+            "Only writing the first {} of ~{} valid seeds, too many to try by hand so consider running the recovery instead\n",
+            Logger::format_num(DUMP_VALID_MAX as u64),
+            Logger::format_num(valid)
+        ));
+    }
+    let file = File::create(path).map_err(|_| format_err!("Unable to create file '{}'", path))?;
+    let mut out = BufWriter::new(file);
+    let mut written = 0;
+    for words in seed.valid_words().take(DUMP_VALID_MAX) {
+        writeln!(out, "{}", words)?;
+        written += 1;
+    }
+    out.flush()?;
+    log.result(
+        "Wrote Seeds: ".dark_green().bold(),
+        &format!("{} to '{}'", written, path),
+    );
+    Ok(())
+}
+
+/// Whether to keep temp files when interrupted, the hashes are needed to restore a session
+fn keep_files(cli: &CliRun) -> bool {
+    cli.keep_files || cli.session.is_some()
+}
+
+/// Prints the keys needed to import the recovered wallet, only once the match derives the address
+fn log_wallet(finished: &Finished, address: &AddressValid, log: &Logger) -> Result<()> {
+    if finished.unverified {
+        return Ok(());
+    }
+    // passphrases with non-printable bytes are decoded from $HEX[...] when deriving the keys
+    if let (Some(seed), Some(passphrase)) = (&finished.seed, &finished.passphrase) {
+        log.result(
+            "Recovered Wallet (keep these private):".dark_green().bold(),
+            "",
+        );
+        for line in address.export_wallet(seed, passphrase)? {
+            log.result(" ".stylize(), &line);
+        }
+        log.println("".stylize());
+    }
+    Ok(())
 }
 
 pub fn log_finished(finished: &Finished, log: &Logger) {
@@ -148,64 +940,282 @@ pub fn log_finished(finished: &Finished, log: &Logger) {
         Finished {
             seed: Some(seed),
             passphrase: Some(passphrase),
+            unverified: true,
+            ..
+        } => {
+            let unverified =
+                "Unverified Match (does not derive your address, please report this bug)";
+            log.result(unverified.dark_red().bold(), "");
+            log.result("Reported Seed: ".dark_red().bold(), seed);
+            if !passphrase.is_empty() {
+                log.result("Reported Passphrase: ".dark_red().bold(), passphrase);
+            }
+        }
+        Finished {
+            seed: Some(seed),
+            passphrase: Some(passphrase),
+            derivation,
+            address,
             ..
         } => {
-            log.print("Found Seed: ".dark_green().bold());
-            log.println(seed.as_str().stylize());
+            log.result("Found Seed: ".dark_green().bold(), seed);
             if !passphrase.is_empty() {
-                log.print("Found Passphrase: ".dark_green().bold());
-                log.println(passphrase.as_str().stylize());
+                log.result("Found Passphrase: ".dark_green().bold(), passphrase);
+            }
+            if let Some(address) = address {
+                log.result("Found Address: ".dark_green().bold(), address);
+            }
+            if let Some(derivation) = derivation {
+                log.result("Found Derivation: ".dark_green().bold(), derivation);
             }
         }
-        _ => log.println_err("Exhausted search with no results...try with different parameters"),
+        Finished {
+            stopped: Some(percent),
+            ..
+        } => {
+            let stopped = format!(
+                "Stopped after searching ~{}% of the guesses, your seed may still be in the rest",
+                percent
+            );
+            log.result(stopped.as_str().dark_yellow().bold(), "");
+            log.println(
+                "See 'Resuming Recoveries' in docs/recovery.md to continue the search".stylize(),
+            );
+        }
+        _ => log.result(
+            "Exhausted search with no results...try with different parameters".dark_red(),
+            "",
+        ),
     }
     log.println("".stylize());
+    for other in &finished.others {
+        log_finished(other, log);
+    }
 }
 
 pub fn configure(cli: &CliRun, log: &Logger) -> Result<Hashcat> {
+    let internet = match cli.no_internet_check {
+        true => None,
+        false => Some(spawn_internet_check(cli.internet_timeout)),
+    };
     let exe = validate_exe()?;
 
-    let seed_arg = cli.seed.clone();
-    let seed = Seed::from_args(&seed_arg, &cli.combinations)?;
+    let mut seed = seed_from_cli(cli)?;
+    if let Some(swap) = &cli.swap {
+        seed = seed.with_swap(swap)?;
+    }
+    if cli.skip_checksum {
+        seed = seed.with_skip_checksum();
+    }
     seed.validate_length()?;
+    seed.validate_skip_checksum()?;
+    if seed.is_slow_count() {
+        log.status("Counting valid seeds...");
+    }
+    let checksums = seed.validate_checksums();
+    log.clear_status();
+    checksums?;
+    if cli.closest_first {
+        seed = seed.with_closest_first();
+    }
+    if let (Some(shards), Some(index)) = (cli.shards, cli.shard_index) {
+        seed = seed.with_shard(shards as usize, index as usize)?;
+    }
+    if cli.passphrase_only {
+        seed.validate_known()?;
+    }
 
-    let address = AddressValid::from_arg(&cli.address, &cli.derivation)?;
+    let addresses = address_args(cli)?;
 
-    let passphrase = match &cli.passphrase {
+    let charsets = vec![
+        cli.custom_charset1.clone(),
+        cli.custom_charset2.clone(),
+        cli.custom_charset3.clone(),
+        cli.custom_charset4.clone(),
+    ];
+    let parse = |args: &[String]| match cli.passphrase_hex {
+        true => Passphrase::from_hex_arg(args, &charsets),
+        false => Passphrase::from_arg(&args.to_vec(), &charsets),
+    };
+    let mut passphrase = match &cli.passphrase {
         None => None,
         Some(args) => {
-            let charsets = vec![
-                cli.custom_charset1.clone(),
-                cli.custom_charset2.clone(),
-                cli.custom_charset3.clone(),
-                cli.custom_charset4.clone(),
-            ];
-            Some(Passphrase::from_arg(args, &charsets)?)
+            Passphrase::validate_dict_sizes(args, cli.max_dict_mb)?;
+            Some(parse(args)?)
         }
     };
+    let mut then_passphrases = vec![];
+    for arg in &cli.then_passphrase {
+        Passphrase::validate_dict_sizes(std::slice::from_ref(arg), cli.max_dict_mb)?;
+        then_passphrases.push(parse(std::slice::from_ref(arg))?);
+    }
+    if cli.passphrase_common {
+        then_passphrases = Passphrase::common()?;
+        passphrase = Some(then_passphrases.remove(0));
+    }
+    if cli.fold_accents {
+        passphrase = passphrase.map(|p| p.with_fold_accents()).transpose()?;
+        for then_passphrase in &mut then_passphrases {
+            *then_passphrase = then_passphrase.with_fold_accents()?;
+        }
+    }
+    if cli.passphrase_prefix.is_some() || cli.passphrase_suffix.is_some() {
+        let prefix = cli.passphrase_prefix.clone().unwrap_or_default();
+        let suffix = cli.passphrase_suffix.clone().unwrap_or_default();
+        passphrase = passphrase
+            .map(|p| p.with_affixes(&prefix, &suffix))
+            .transpose()?;
+        for then_passphrase in &mut then_passphrases {
+            *then_passphrase = then_passphrase.with_affixes(&prefix, &suffix)?;
+        }
+    }
+    if let Some(hcstat2) = &cli.markov_hcstat2 {
+        let mut masks = 0;
+        for passphrase in passphrase.iter_mut().chain(then_passphrases.iter_mut()) {
+            if passphrase.has_mask() {
+                *passphrase = passphrase.with_markov(hcstat2)?;
+                masks += 1;
+            }
+        }
+        if masks == 0 {
+            bail!("Markov stats only apply to mask attacks, add a mask such as '?l?l?l?d' to your passphrase");
+        }
+    }
 
     log.heading("Seedcat Configuration");
-    let format_address = format!("{} ({}) Address: ", address.kind.key, address.kind.name);
-    log.print(format_address.as_str().bold());
-    log.println(format!("{}\n", address.formatted).as_str().stylize());
-    log.format_attempt("Derivations", &address.derivations);
+    if let Some(config) = &cli.config {
+        log.print("Config File: ".bold());
+        log.println(format!("{}\n", config).as_str().stylize());
+    }
+    for address in &addresses {
+        let format_address = format!("{} ({}) Address: ", address.kind.key, address.kind.name);
+        log.print(format_address.as_str().bold());
+        log.println(format!("{}\n", address.formatted).as_str().stylize());
+        if cli.xpub_preview {
+            log.println("XPUB Derived Addresses (compare with your wallet):".bold());
+            for line in address.xpub_addresses(XPUB_PREVIEW_ADDRESSES)? {
+                log.println(format!(" {}", line).as_str().stylize());
+            }
+            log.println("".stylize());
+        }
+        log.format_attempt("Derivations", &address.derivations);
+        log_batches(address, log);
+    }
     log.format_attempt("Seeds", &seed);
+    if let (Some(shards), Some(index)) = (cli.shards, cli.shard_index) {
+        log.print("Shard: ".bold());
+        let shard = format!(
+            "{} of {} (run indexes 0 to {} on separate machines)\n",
+            index,
+            shards,
+            shards - 1
+        );
+        log.println(shard.as_str().stylize());
+    }
+    if cli.skip_checksum {
+        log.println_warn("Skipping checksums guesses every seed, use only if your wallet ignored the BIP39 checksum\n");
+    }
+    if let Some(warning) = seed.combinations_warning() {
+        log.println_warn(&format!("{}\n", warning));
+    }
     if let Some(passphrase) = &passphrase {
         log.format_attempt("Passphrases", passphrase);
+        if let Some(warning) = passphrase.dict_warning() {
+            log.println_warn(&format!("{}\n", warning));
+        }
+        for warning in passphrase.charset_warnings() {
+            log.println_warn(&format!("{}\n", warning));
+        }
+        for passphrase in [passphrase].into_iter().chain(&then_passphrases) {
+            match passphrase.control_bytes_warning() {
+                Some(warning) if !cli.allow_control_bytes => {
+                    log.println_warn(&format!("{}\n", warning))
+                }
+                _ => {}
+            }
+        }
+    } else if then_passphrases.is_empty() {
+        // omitting --passphrase assumes the wallet has none rather than ignoring passphrases
+        log.print("Passphrase: ".bold());
+        log.println("none (empty)".stylize());
+        log.println(
+            " Only seeds without a passphrase are checked, pass --passphrase if your wallet has one\n"
+                .stylize(),
+        );
     }
-
-    if seed.valid_seeds() == 0 {
-        bail!("All possible seeds have invalid checksums")
+    for passphrase in &then_passphrases {
+        log.format_attempt("Then Passphrases", passphrase);
     }
+
+    let slow_count = seed.is_slow_count();
     let args = cli.hashcat.clone();
-    let hashcat = Hashcat::new(exe, address.clone(), seed, passphrase, args);
+    let mut hashcat = Hashcat::new(exe, addresses, seed, passphrase, args);
+    hashcat.set_then_passphrases(then_passphrases);
+    if let Some(session) = &cli.session {
+        hashcat.set_session(session.clone());
+    }
+    if cli.passphrase_only {
+        hashcat.force_pure_gpu();
+    }
+    hashcat.set_compression(cli.compression);
+    hashcat.set_hash_mode(cli.hashcat_mode);
+    if cli.hashcat_mode != DEFAULT_HASH_MODE {
+        log.println_warn(&format!(
+            "Using hashcat mode {} instead of {}, make sure your hashcat supports it\n",
+            cli.hashcat_mode, DEFAULT_HASH_MODE
+        ));
+    }
+    let resuming = ["-s", "--skip", "--restore"];
+    let resuming = cli.hashcat.iter().any(|arg| {
+        let arg = arg.split('=').next().unwrap_or_default();
+        resuming.contains(&arg)
+    });
+    hashcat.set_ordered(cli.ordered || resuming);
+    hashcat.set_candidate_files(cli.candidate_files);
+    hashcat.set_find_all(cli.find_all);
+    match (cli.single_mode, cli.no_single_mode) {
+        (true, _) => hashcat.set_s_mode(Some(true)),
+        (_, true) => hashcat.set_s_mode(Some(false)),
+        _ => {}
+    }
 
     if hashcat.total() == u64::MAX {
         bail!("Exceeding 2^64 attempts will take forever to run, try reducing combinations");
     }
+    let exe = hashcat.exe();
+    log.println_debug(&format!("Hashcat Path: {}", exe.path().display()));
+    log.println_debug(&format!("Seedcat Folder: {}", exe.seedcat_dir().display()));
+    log.println_debug(&format!("Temp Files: {}", hashcat.temp_files().display()));
+    if cli.stdout_candidates.is_none() && cli.preview.is_none() {
+        log_devices(exe, log);
+    }
+
     log.print_num("Total Guesses: ", hashcat.total());
+    log.println(
+        format!(" Breakdown: {}", hashcat.total_breakdown())
+            .as_str()
+            .stylize(),
+    );
+    log.println(
+        format!(" Hashcat Session: {}", hashcat.session())
+            .as_str()
+            .stylize(),
+    );
 
-    let mode = hashcat.get_mode()?;
+    if slow_count {
+        log.status("Choosing the hashcat mode...");
+    }
+    let mode = hashcat.get_mode();
+    log.clear_status();
+    let mode = mode?;
+    log.println_debug(&format!(
+        "Chose {} mode with {} hashes and {} passphrases (max hashes {}, min passphrases {})",
+        mode.runner.name(),
+        mode.hashes,
+        mode.passphrases,
+        hashcat.max_hashes,
+        hashcat.min_passphrases
+    ));
     match mode.runner {
         HashcatRunner::PureGpu => {
             log.print(" Pure GPU Mode: Can run on large GPU clusters\n".stylize())
@@ -220,14 +1230,34 @@ pub fn configure(cli: &CliRun, log: &Logger) -> Result<Hashcat> {
             " Stdin Mode: CPU-limited due to not enough passphrases to guess\n".dark_yellow(),
         ),
     }
-    if has_internet() {
-        log.println(
-            " Warning: For better security turn off your internet connection".dark_yellow(),
+    if !matches!(mode.runner, HashcatRunner::BinaryCharsets(_, _)) {
+        if let Some(warning) = hashcat.binary_slots_warning() {
+            log.println_warn(&format!(" {}", warning));
+        }
+    }
+    let (s_mode, reason) = hashcat.s_mode(&mode);
+    let s_mode = match s_mode {
+        true => format!(" Hashcat -S Mode: On ({})\n", reason),
+        false => format!(" Hashcat -S Mode: Off ({})\n", reason),
+    };
+    log.print(s_mode.as_str().stylize());
+    if internet.map(|check| check.join().unwrap_or(false)) == Some(true) {
+        log.println_warn(" Warning: For better security turn off your internet connection");
+    }
+
+    let estimate = hashcat.total() / ESTIMATED_GUESSES_PER_SEC;
+    let infeasible = estimate > INFEASIBLE_SECS;
+    if infeasible {
+        let warning = format!(
+            " Warning: Estimated to take {} on a high-end GPU, try reducing the guesses",
+            Timer::format_time(estimate)
         );
+        log.println_warn(&warning);
     }
 
-    if !cli.skip_prompt {
-        prompt_continue(log);
+    let to_stdout = cli.stdout_candidates.as_deref() == Some("-");
+    if !cli.skip_prompt && !cli.quiet && !to_stdout && cli.preview.is_none() {
+        prompt_continue(log, infeasible);
     }
 
     log.heading("Seedcat Recovery");
@@ -235,20 +1265,154 @@ pub fn configure(cli: &CliRun, log: &Logger) -> Result<Hashcat> {
     Ok(hashcat)
 }
 
-fn has_internet() -> bool {
-    // See if we can connect to Google
-    let socket = SocketAddr::from_str("209.85.233.101:80").expect("Valid socket");
-    TcpStream::connect_timeout(&socket, Duration::from_millis(100)).is_ok()
+/// Prints the devices hashcat detects so users can check their GPUs will be used
+fn log_devices(exe: &HashcatExe, log: &Logger) {
+    let devices = match exe.devices() {
+        Ok(devices) => devices,
+        Err(err) => return log.println_warn(&format!("Warning: {}\n", err)),
+    };
+    log.println("Devices:".bold());
+    for device in &devices {
+        let kind = if device.is_cpu { " (CPU)" } else { "" };
+        let line = format!(" #{} {}{}", device.id, device.name, kind);
+        log.println(line.as_str().stylize());
+    }
+    if devices.iter().all(|device| device.is_cpu) {
+        log.println_warn(" Warning: No GPU detected so recovery will be slow");
+    }
+    log.println("".stylize());
+}
+
+/// The BIP39 wordlist unless a custom --wordlist file is given
+fn wordlist_arg(path: &Option<String>) -> Result<Wordlist> {
+    match path {
+        None => Ok(bip39_wordlist()),
+        Some(path) => load_wordlist(path),
+    }
+}
+
+/// Parses the --seed words or reads the --seed-list of complete seeds
+fn seed_from_cli(cli: &CliRun) -> Result<Seed> {
+    if let Some(path) = &cli.seed_list {
+        let list = fs::read_to_string(path)
+            .map_err(|_| format_err!("Unable to read seed list '{}'", path))?;
+        return Seed::from_list(&list, &bip39_wordlist());
+    }
+    let seed = seed_arg(cli.seed.as_deref().unwrap_or_default(), &cli.order)?;
+    Seed::from_args(&seed, &cli.combinations)
+}
+
+/// The --seed words, reordered into the grid columns if --order is given
+fn seed_arg(seed: &str, order: &Option<String>) -> Result<String> {
+    match order {
+        None => Ok(seed.to_string()),
+        Some(order) => Seed::reorder_args(seed, order),
+    }
+}
+
+/// Addresses from the CLI, trying every address type if the HASH160 type is 'any'
+fn address_args(cli: &CliRun) -> Result<Vec<AddressValid>> {
+    let any = cli.address_type.as_ref().map(|kind| kind.to_lowercase()) == Some("any".into());
+    let derivation = match cli.bip {
+        None => cli.derivation.clone(),
+        Some(bip) => Some(AddressValid::bip_derivation(bip, cli.account, cli.index)?),
+    };
+    let mut addresses = match (&cli.hash160, any) {
+        (Some(hash160), true) => AddressValid::from_hash160_any(hash160, &derivation)?,
+        (None, true) => {
+            bail!("Address type 'any' requires --hash160 since addresses already imply their type")
+        }
+        _ => vec![AddressValid::from_arg(&address_arg(cli)?, &derivation)?],
+    };
+    if let Some(bip) = cli.bip {
+        addresses.retain(|address| address.is_bip(bip));
+        if addresses.is_empty() {
+            bail!(
+                "BIP{} wallets do not use this type of address, check the --bip of your wallet",
+                bip
+            );
+        }
+    }
+    if cli.include_change {
+        addresses = addresses
+            .iter()
+            .map(|a| a.with_change())
+            .collect::<Result<_>>()?;
+    }
+    match &cli.address_range {
+        None => Ok(addresses),
+        Some(range) => addresses
+            .iter()
+            .map(|address| address.with_address_range(range))
+            .collect(),
+    }
+}
+
+/// Address from the CLI, converting raw HASH160 or scriptPubKey inputs
+fn address_arg(cli: &CliRun) -> Result<String> {
+    match (
+        &cli.address,
+        &cli.hash160,
+        &cli.script_pubkey,
+        &cli.address_type,
+    ) {
+        (Some(address), _, _, _) => Ok(address.clone()),
+        (_, Some(hash160), _, Some(kind)) => AddressValid::from_hash160(hash160, kind),
+        (_, _, Some(script), _) => AddressValid::from_script_pubkey(script),
+        _ => bail!("Specify the --address or its --hash160 and --address-type"),
+    }
 }
 
-fn prompt_continue(log: &Logger) {
-    log.print("\nContinue with recovery [Y/n]? ".stylize());
-    io::stdout().flush().unwrap();
+/// Shows which derivations hashcat explodes inside each hash line
+fn log_batches(address: &AddressValid, log: &Logger) {
+    let batches = address.derivations.batches();
+    if address.derivations.hash_ratio() <= 1.0 {
+        return;
+    }
+    let heading = format!(
+        "Derivation Hash Lines: {} (~{:.1} derivations per line)\n",
+        batches.len(),
+        address.derivations.hash_ratio()
+    );
+    log.print(heading.as_str().bold());
+    for (arg, total) in batches.iter().take(MAX_BATCHES_LOGGED) {
+        log.println(format!(" {} ({})", arg, total).as_str().stylize());
+    }
+    if batches.len() > MAX_BATCHES_LOGGED {
+        let more = format!(" ...and {} more", batches.len() - MAX_BATCHES_LOGGED);
+        log.println(more.as_str().stylize());
+    }
+    log.println("".stylize());
+}
+
+/// Checks in the background so slow networks don't stall the configuration
+fn spawn_internet_check(millis: u64) -> JoinHandle<bool> {
+    thread::spawn(move || has_internet(Duration::from_millis(millis)))
+}
+
+fn has_internet(timeout: Duration) -> bool {
+    // See if we can connect to any well-known host
+    INTERNET_HOSTS.iter().any(|host| {
+        let socket = SocketAddr::from_str(host).expect("Valid socket");
+        TcpStream::connect_timeout(&socket, timeout).is_ok()
+    })
+}
+
+/// Asks to continue, requiring an explicit 'yes' if the recovery will likely never finish
+fn prompt_continue(log: &Logger, infeasible: bool) {
+    match infeasible {
+        true => log.prompt("\nType 'yes' to continue with recovery anyway [yes/N]? ".stylize()),
+        false => log.prompt("\nContinue with recovery [Y/n]? ".stylize()),
+    }
+    io::stderr().flush().unwrap();
     let mut line = String::new();
     let stdin = io::stdin();
     stdin.lock().read_line(&mut line).unwrap();
+    if infeasible && line.trim().to_lowercase() != "yes" {
+        exit(EXIT_ABORTED);
+    }
     if line.contains("n") {
-        exit(0);
+        exit(EXIT_ABORTED);
     }
 }
 
@@ -259,16 +1423,77 @@ fn validate_exe() -> Result<HashcatExe> {
         p => bail!("Unable to identify '{}' family", p),
     };
 
-    let hashcat = Path::new(HASHCAT_PATH);
-    for executable in [platform, "hashcat"] {
-        if hashcat.join(executable).exists() {
-            if let Ok(exe) = std::fs::canonicalize(hashcat.join(executable)) {
-                return Ok(HashcatExe::new(exe));
+    let mut searched = vec![];
+    for folder in hashcat_folders() {
+        for executable in [platform, "hashcat"] {
+            let path = folder.join(executable);
+            if path.is_file() {
+                if let Ok(exe) = std::fs::canonicalize(&path) {
+                    return Ok(HashcatExe::new(exe));
+                }
             }
+            searched.push(format!("  {}", path.display()));
         }
     }
     bail!(
-        "Could not find executable '{}'...make sure you are running in 'seedcat' directory",
-        hashcat.join(platform).to_str().unwrap(),
+        "Could not find the hashcat executable, searched:\n{}\nRun in the 'seedcat' directory or set {} to the hashcat folder",
+        searched.join("\n"),
+        HASHCAT_ENV
     );
 }
+
+/// Resolves a seedcat resource relative to the current directory, else the executable's folder
+pub fn resource_path(path: &Path) -> PathBuf {
+    if path.exists() || path.is_absolute() {
+        return path.to_path_buf();
+    }
+    let exe_dir = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    match exe_dir.map(|dir| dir.join(path)) {
+        Some(resolved) if resolved.exists() => resolved,
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Folders that may contain hashcat in order of precedence
+fn hashcat_folders() -> Vec<PathBuf> {
+    let mut folders = vec![];
+    if let Some(path) = env::var_os(HASHCAT_ENV) {
+        let path = PathBuf::from(path);
+        match path.is_file() {
+            true => folders.extend(path.parent().map(Path::to_path_buf)),
+            false => folders.push(path),
+        }
+    }
+    folders.push(PathBuf::from(HASHCAT_PATH));
+    if let Ok(exe) = env::current_exe() {
+        folders.extend(exe.parent().map(|dir| dir.join(HASHCAT_PATH)));
+    }
+    // a system hashcat on the PATH only works if it was built with the seedcat module
+    if let Some(paths) = env::var_os("PATH") {
+        for dir in env::split_paths(&paths) {
+            for executable in ["hashcat", "hashcat.exe"] {
+                let Ok(exe) = std::fs::canonicalize(dir.join(executable)) else {
+                    continue;
+                };
+                let folder = exe.parent().map(Path::to_path_buf).unwrap_or_default();
+                if has_seedcat_module(&folder) && !folders.contains(&folder) {
+                    folders.push(folder);
+                }
+            }
+        }
+    }
+    folders
+}
+
+/// Whether the hashcat folder contains the module for the seedcat hash mode
+fn has_seedcat_module(folder: &Path) -> bool {
+    let module = format!("module_{}.", DEFAULT_HASH_MODE);
+    match std::fs::read_dir(folder.join("modules")) {
+        Ok(entries) => entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with(&module)),
+        Err(_) => false,
+    }
+}
@@ -1,15 +1,17 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
 use std::io;
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, format_err, Error, Result};
+use flate2::read::MultiGzDecoder;
 
 use crate::combination::Combinations;
 use crate::logger::{Attempt, Logger};
-use crate::{HASHCAT_PATH, SEPARATOR};
+use crate::{resource_path, DEFAULT_COMPRESSION, HASHCAT_PATH, SEPARATOR};
 
-const ERR_MSG: &str = "\nPassphrase takes at most 2 args with the following possibilities:
+pub const ERR_MSG: &str = "\nPassphrase takes at most 2 args with the following possibilities:
   DICT attack:            --passphrase 'prefix,./dicts/dict.txt,suffix'
   MASK attack:            --passphrase 'prefix?l?l?l?d?d?1suffix'
   DICT DICT attack:       --passphrase './dicts/dict.txt,deliminator' './dicts/dict.txt'
@@ -22,6 +24,24 @@ const ERR_MSG: &str = "\nPassphrase takes at most 2 args with the following poss
   To escape special characters '?' ',' '/' just double them, e.g. '??' ',,' '//'\n";
 
 const MAX_DICT: u64 = 1_000_000_000;
+/// Custom charset slots the binary charsets use to guess the last seed word
+const BINARY_CHARSETS: usize = 3;
+/// Mask positions shown in the begin and end examples before they are cut off
+const MAX_EXAMPLE_LEN: usize = 100;
+pub const DEFAULT_MAX_DICT_MB: u64 = 1000;
+/// Patterns commonly used for passphrases tried by `--passphrase-common`
+const COMMON_PASSPHRASES: [&[&str]; 10] = [
+    &["./dicts/10k.txt"],
+    &["./dicts/10k_cap.txt"],
+    &["./dicts/10k_upper.txt"],
+    &["?d?d?d?d"],
+    &["./dicts/10k.txt", "?d?d"],
+    &["./dicts/10k_cap.txt", "?d?d"],
+    &["./dicts/1k.txt", "./dicts/1k.txt"],
+    &["./dicts/1k_cap.txt", "./dicts/1k_cap.txt"],
+    &["?d?d?d?d?d?d"],
+    &["./dicts/10k_cap.txt", "?d?d?d?d"],
+];
 const HC_LEFT_DICT: &str = "_left.gz";
 const HC_RIGHT_DICT: &str = "_right.gz";
 
@@ -31,6 +51,8 @@ pub struct Passphrase {
     left: PassphraseArg,
     right: Option<PassphraseArg>,
     charsets: UserCharsets,
+    compression: u32,
+    markov_hcstat2: Option<PathBuf>,
 }
 
 impl Attempt for Passphrase {
@@ -74,6 +96,8 @@ impl Passphrase {
             left: args.next().expect("at least one arg"),
             right: args.next(),
             charsets,
+            compression: DEFAULT_COMPRESSION,
+            markov_hcstat2: None,
         }
     }
 
@@ -89,11 +113,11 @@ impl Passphrase {
         result.push("-a".to_string());
         result.push(self.attack_mode.to_string());
         let dict = prefix.to_string() + HC_LEFT_DICT;
-        result.push(Self::build_arg(&self.left, dict, log).await?);
+        result.extend(Self::build_arg(&self.left, dict, self.compression, log).await?);
 
         if let Some(right) = &self.right {
             let dict = prefix.to_string() + HC_RIGHT_DICT;
-            result.push(Self::build_arg(right, dict, log).await?);
+            result.extend(Self::build_arg(right, dict, self.compression, log).await?);
         }
 
         for charset in self.charsets.to_wildcards() {
@@ -101,15 +125,99 @@ impl Passphrase {
             result.push(charset.charset.unwrap().to_string());
         }
 
+        if self.has_mask() {
+            if let Some(hcstat2) = &self.markov_hcstat2 {
+                result.push(format!("--markov-hcstat2={}", hcstat2.display()));
+            }
+        }
+
+        if self.charsets.hex {
+            let args = [Some(&self.left), self.right.as_ref()];
+            if args
+                .iter()
+                .flatten()
+                .any(|a| matches!(a, PassphraseArg::Mask(_)))
+            {
+                result.push("--hex-charset".to_string());
+            }
+            if args
+                .iter()
+                .flatten()
+                .any(|a| matches!(a, PassphraseArg::Dict(_)))
+            {
+                result.push("--hex-wordlist".to_string());
+            }
+        }
+
         Ok(result)
     }
 
+    /// Sets the gzip level for dictionaries written for hashcat
+    pub fn with_compression(&self, compression: u32) -> Self {
+        let mut copy = self.clone();
+        copy.compression = compression;
+        copy
+    }
+
+    /// Adds the accentless variant of every dictionary entry e.g. 'café' also tries 'cafe'
+    pub fn with_fold_accents(&self) -> Result<Self> {
+        if self.charsets.hex {
+            bail!("Accents cannot be folded in hex dictionaries");
+        }
+        let mut copy = self.clone();
+        for arg in [Some(&mut copy.left), copy.right.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            if let PassphraseArg::Dict(dict) = arg {
+                *dict = dict.fold_accents();
+            }
+        }
+        Ok(copy)
+    }
+
+    /// Wraps every candidate with a known prefix and suffix e.g. 'satoshi' + dict + '2009'
+    pub fn with_affixes(&self, prefix: &str, suffix: &str) -> Result<Self> {
+        if self.charsets.hex {
+            bail!("A prefix or suffix cannot be added to hex passphrases");
+        }
+        let mut copy = self.clone();
+        copy.left = copy.left.with_affixes(prefix, "");
+        match copy.right {
+            Some(right) => copy.right = Some(right.with_affixes("", suffix)),
+            None => copy.left = copy.left.with_affixes("", suffix),
+        }
+        Ok(copy)
+    }
+
+    /// Whether any arg is a mask that hashcat orders by Markov statistics
+    pub fn has_mask(&self) -> bool {
+        [Some(&self.left), self.right.as_ref()]
+            .iter()
+            .flatten()
+            .any(|a| matches!(a, PassphraseArg::Mask(_)))
+    }
+
+    /// Orders the mask candidates with hashcat's Markov stats file
+    pub fn with_markov(&self, hcstat2: &str) -> Result<Self> {
+        let mut copy = self.clone();
+        // hashcat runs from its own folder so the path must be absolute
+        let path = PathBuf::from(hcstat2).canonicalize();
+        let path =
+            path.map_err(|_| format_err!("Unable to find Markov stats file '{}'", hcstat2))?;
+        copy.markov_hcstat2 = Some(path);
+        Ok(copy)
+    }
+
     pub fn add_binary_charsets(&self, guesses: usize, entropy_bits: usize) -> Result<Option<Self>> {
+        if !self.allows_binary_charsets() {
+            return Ok(None);
+        }
         let mut copy = self.clone();
 
         let wildcards = copy.charsets.add_binary_charsets(entropy_bits)?;
         // Unable to generate the 3 wildcards required
-        if wildcards.len() != 3 {
+        if wildcards.len() != BINARY_CHARSETS {
             return Ok(None);
         }
         if let PassphraseArg::Dict(d) = &self.left {
@@ -132,24 +240,121 @@ impl Passphrase {
         Ok(None)
     }
 
-    async fn build_arg(arg: &PassphraseArg, dictname: String, log: &Logger) -> Result<String> {
+    fn allows_binary_charsets(&self) -> bool {
+        // Binary charset files would be interpreted as hex
+        // Sharded dictionaries cannot be used in a hybrid attack
+        !self.charsets.hex && self.dict_warning().is_none()
+    }
+
+    /// Explains which custom charset slots binary charsets need if the user's charsets took them
+    pub fn binary_slots_warning(&self) -> Option<String> {
+        match self.allows_binary_charsets() {
+            true => self.charsets.binary_slots_warning(),
+            false => None,
+        }
+    }
+
+    async fn build_arg(
+        arg: &PassphraseArg,
+        dictname: String,
+        compression: u32,
+        log: &Logger,
+    ) -> Result<Vec<String>> {
         Ok(match arg {
-            PassphraseArg::Mask(m) => m.arg.clone(),
+            PassphraseArg::Mask(m) => vec![m.arg.clone()],
             PassphraseArg::Dict(d) => {
                 let mut dict = d.clone();
-                dict.combinations.write_zip(&dictname, log).await?;
-                dictname
+                let combinations = &mut dict.combinations;
+                // hashcat runs each dictionary shard in order
+                combinations
+                    .write_zip(&dictname, MAX_DICT, compression, log)
+                    .await?
             }
         })
     }
 
+    /// Warns about custom charsets whose duplicate characters don't add to the guesses
+    pub fn charset_warnings(&self) -> &Vec<String> {
+        &self.charsets.duplicates
+    }
+
+    /// Warns if a mask guesses control bytes that are unlikely to be typed in a passphrase
+    pub fn control_bytes_warning(&self) -> Option<String> {
+        for arg in [Some(&self.left), self.right.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            let PassphraseArg::Mask(mask) = arg else {
+                continue;
+            };
+            let mut chars = mask.arg.chars();
+            while let Some(c) = chars.next() {
+                if c != '?' {
+                    continue;
+                }
+                let Some(flag) = chars.next() else {
+                    break;
+                };
+                if self.charsets.has_control_bytes(flag) {
+                    return Some(format!(
+                        "Warning: Mask '{}' guesses control bytes such as 0x00 with '?{}' that are unlikely in a typed passphrase\n Use '?a' for every printable character instead of '?b' (or pass --allow-control-bytes to hide this warning)",
+                        mask.arg, flag
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// Warns if the dictionary is large enough to be split into shards
+    pub fn dict_warning(&self) -> Option<String> {
+        match &self.left {
+            PassphraseArg::Dict(d) if d.total() > MAX_DICT => Some(format!(
+                "Warning: Dictionary exceeds 1B combinations so it will be split into {} files run in order",
+                d.total().div_ceil(MAX_DICT)
+            )),
+            _ => None,
+        }
+    }
+
     pub fn from_arg(args: &Vec<String>, charsets: &Vec<Option<String>>) -> Result<Passphrase> {
-        let charsets = UserCharsets::new(charsets.clone())?;
+        Self::parse(args, UserCharsets::new(charsets.clone())?)
+    }
+
+    /// Common passphrase patterns in order of increasing guesses
+    pub fn common() -> Result<Vec<Passphrase>> {
+        let mut passphrases = vec![];
+        for args in COMMON_PASSPHRASES {
+            let args = args.iter().map(|arg| arg.to_string()).collect();
+            passphrases.push(Self::from_arg(&args, &vec![])?);
+        }
+        passphrases.sort_by_key(|passphrase| passphrase.total());
+        Ok(passphrases)
+    }
+
+    /// Parses args where dictionaries, masks and charsets contain hex-encoded bytes
+    pub fn from_hex_arg(args: &[String], charsets: &[Option<String>]) -> Result<Passphrase> {
+        Self::parse(args, UserCharsets::new_hex(charsets.to_vec())?)
+    }
+
+    fn parse(args: &[String], charsets: UserCharsets) -> Result<Passphrase> {
         let mut parsed = vec![];
         for arg in args {
             parsed.push(Self::validate_arg(arg, &charsets)?);
         }
 
+        // Only a single dictionary can be split into multiple files for hashcat
+        for (arg, parsed) in args.iter().zip(&parsed) {
+            if let PassphraseArg::Dict(d) = parsed {
+                if args.len() > 1 && d.total() > MAX_DICT {
+                    bail!(
+                        "Dictionaries '{}' exceed 1B combinations\n  Try reducing size or using a single arg",
+                        arg
+                    );
+                }
+            }
+        }
+
         let passphrase = match parsed[..] {
             [PassphraseArg::Mask(_)] => Passphrase::new(3, parsed, charsets),
             [PassphraseArg::Dict(_)] => Passphrase::new(0, parsed, charsets),
@@ -172,28 +377,193 @@ impl Passphrase {
         if arg.replace("??", "").contains("?") {
             Ok(PassphraseArg::Mask(Self::mask(arg, &charsets)?))
         } else {
-            Ok(PassphraseArg::Dict(Self::dict(arg)?))
+            Ok(PassphraseArg::Dict(Self::dict(arg, charsets.hex)?))
+        }
+    }
+
+    /// Fails if any dictionary file is too large to load into memory
+    pub fn validate_dict_sizes(args: &[String], max_mb: u64) -> Result<()> {
+        for arg in args.iter().flat_map(|arg| arg.split(SEPARATOR)) {
+            let Some(path) = Self::dict_path(arg) else {
+                continue;
+            };
+            let Some(bytes) = Self::dict_bytes(&path, max_mb) else {
+                continue;
+            };
+            if bytes > max_mb.saturating_mul(1_000_000) {
+                bail!(
+                    "Dictionary '{:?}' is {}MB which exceeds the {}MB limit of files loaded into memory\n  Check the path is correct or raise the limit with --max-dict-mb",
+                    path,
+                    bytes.div_ceil(1_000_000),
+                    max_mb
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Size of the dictionary once loaded, gzip files are decompressed up to just over the limit
+    fn dict_bytes(path: &Path, max_mb: u64) -> Option<u64> {
+        let path = resource_path(path);
+        match path.extension() {
+            Some(ext) if ext == "gz" => {
+                let file = File::open(&path).ok()?;
+                let limit = max_mb.saturating_add(1).saturating_mul(1_000_000);
+                let mut reader = MultiGzDecoder::new(file).take(limit);
+                io::copy(&mut reader, &mut io::sink()).ok()
+            }
+            _ => path.metadata().ok().map(|metadata| metadata.len()),
         }
     }
 
-    fn dict(arg: &str) -> Result<Dictionary> {
+    /// The first passphrases in the order they are generated, bytes that aren't UTF-8 are shown as $HEX[...]
+    pub fn preview(&self, num: usize) -> Result<Vec<String>> {
+        let left = self.preview_arg(&self.left, num)?;
+        let right = match &self.right {
+            Some(right) => self.preview_arg(right, num)?,
+            None => vec![vec![]],
+        };
+        let mut result = vec![];
+        for left in &left {
+            for right in &right {
+                if result.len() == num {
+                    return Ok(result);
+                }
+                let bytes = [left.clone(), right.clone()].concat();
+                result.push(match String::from_utf8(bytes.clone()) {
+                    Ok(str) if !self.charsets.hex => str,
+                    _ => format!(
+                        "$HEX[{}]",
+                        bytes
+                            .iter()
+                            .map(|b| format!("{:02x}", b))
+                            .collect::<String>()
+                    ),
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    /// Whether any passphrase can contain a newline byte, which would split stdin candidates
+    pub fn has_newline_bytes(&self) -> Result<bool> {
+        let mut args = vec![&self.left];
+        args.extend(self.right.iter());
+        for arg in args {
+            for element in self.arg_elements(arg)? {
+                if element.iter().flatten().any(|b| *b == b'\n' || *b == b'\r') {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn preview_arg(&self, arg: &PassphraseArg, num: usize) -> Result<Vec<Vec<u8>>> {
+        let mut combinations = Combinations::new(self.arg_elements(arg)?);
+        let mut result = vec![];
+        while let Some(next) = combinations.next() {
+            if result.len() == num {
+                break;
+            }
+            result.push(next.concat());
+        }
+        Ok(result)
+    }
+
+    /// The possible bytes of every element of the arg, in order
+    fn arg_elements(&self, arg: &PassphraseArg) -> Result<Vec<Vec<Vec<u8>>>> {
+        let hex = self.charsets.hex;
+        let decode = |str: &str| -> Result<Vec<u8>> {
+            if !hex {
+                return Ok(str.as_bytes().to_vec());
+            }
+            let bytes = (0..str.len()).step_by(2);
+            Ok(bytes
+                .map(|i| u8::from_str_radix(&str[i..i + 2], 16))
+                .collect::<Result<_, _>>()?)
+        };
+        let mut elements: Vec<Vec<Vec<u8>>> = vec![];
+        match arg {
+            PassphraseArg::Dict(dict) => {
+                for element in dict.combinations.elements() {
+                    elements.push(element.iter().map(|s| decode(s)).collect::<Result<_>>()?);
+                }
+            }
+            PassphraseArg::Mask(mask) => {
+                let mut chars = mask.arg.chars();
+                let mut literal = String::new();
+                while let Some(c) = chars.next() {
+                    if c != '?' {
+                        literal.push(c);
+                        continue;
+                    }
+                    elements.push(vec![decode(&literal)?]);
+                    literal.clear();
+                    let bytes = match chars.next() {
+                        Some('?') => vec![b'?'],
+                        Some(flag) => {
+                            match self.charsets.to_wildcards().iter().find(|w| w.flag == flag) {
+                                Some(Wildcard {
+                                    charset: Some(charset),
+                                    ..
+                                }) => {
+                                    let num = flag.to_digit(10).unwrap_or_default() as usize;
+                                    Wildcard::custom_bytes(num, charset, hex)?
+                                }
+                                _ => Wildcard::custom_bytes(0, &format!("?{}", flag), false)?,
+                            }
+                        }
+                        None => bail!("Mask '{}' ends in a ? use ?? to escape", mask.arg),
+                    };
+                    elements.push(bytes.into_iter().map(|b| vec![b]).collect());
+                }
+                elements.push(vec![decode(&literal)?]);
+            }
+        }
+        Ok(elements)
+    }
+
+    fn dict_path(arg: &str) -> Option<PathBuf> {
+        if arg.starts_with("./") && !arg.starts_with(".//") {
+            return Some(PathBuf::from_iter(arg.split("/")));
+        }
+        None
+    }
+
+    fn dict(arg: &str, hex: bool) -> Result<Dictionary> {
         let mut combinations: Vec<Vec<String>> = vec![];
         for arg in arg.split(SEPARATOR) {
-            if arg.starts_with("./") && !arg.starts_with(".//") {
-                let path = PathBuf::from_iter(arg.split("/").into_iter());
+            if let Some(path) = Self::dict_path(arg) {
                 let err = format_err!("Failed to read file '{:?}'{}", path, ERR_MSG);
-                let file = File::open(path).map_err(|_| err)?;
-                let str = io::read_to_string(file).map_err(Error::msg)?;
-                let bytes = str.lines().map(String::from).collect();
+                let file = File::open(resource_path(&path)).map_err(|_| err)?;
+                let reader: Box<dyn BufRead> = match path.extension() {
+                    Some(ext) if ext == "gz" => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+                    _ => Box::new(BufReader::new(file)),
+                };
+                // every line is kept in memory, reading lines only avoids also buffering the whole file
+                let lines = reader.lines();
+                let bytes: Vec<String> = lines.collect::<Result<_, _>>().map_err(Error::msg)?;
+                if let Some(line) = bytes.iter().find(|line| hex && !is_hex(line)) {
+                    bail!(
+                        "Dictionary '{:?}' contains '{}' which is not hex",
+                        path,
+                        line
+                    );
+                }
                 combinations.push(bytes);
-            } else if arg == "" {
+            } else if arg.is_empty() && hex {
+                combinations.push(vec!["2c".to_string()]);
+            } else if arg.is_empty() {
                 combinations.push(vec![",".to_string()]);
+            } else if hex && !is_hex(arg) {
+                bail!("Dictionary arg '{}' is not hex", arg);
             } else {
                 let replaced = arg.replace("??", "?").replace("//", "/");
                 combinations.push(vec![replaced.to_string()]);
             }
         }
-        Ok(Dictionary::new(combinations, arg)?)
+        Ok(Dictionary::new(combinations))
     }
 
     fn mask(arg: &str, charsets: &UserCharsets) -> Result<Mask> {
@@ -203,23 +573,43 @@ impl Passphrase {
         let wildcards = wildcards(charsets)?;
         let mut question = false;
         let mut combinations = 1_u64;
+        let mut literals = vec![String::new()];
+        let mut positions = 0;
         for c in arg.chars() {
+            // only the start of a very long mask is shown in the examples
+            let shown = positions < MAX_EXAMPLE_LEN;
+            if c != '?' || question {
+                positions += 1;
+            }
             if question {
+                literals.push(String::new());
                 let wildcard = wildcards.get(&c).ok_or(Self::wildcard_err(c, &wildcards))?;
-                example_start.push(wildcard.example_start.clone());
-                example_end.push(wildcard.example_end.clone());
+                if shown {
+                    example_start.push(wildcard.example_start.clone());
+                    example_end.push(wildcard.example_end.clone());
+                }
                 combinations = combinations.saturating_mul(wildcard.length);
                 question = false;
             } else if c == '?' {
                 question = true;
             } else {
-                example_start.push(c.to_string());
-                example_end.push(c.to_string());
+                literals.last_mut().expect("not empty").push(c);
+                if shown {
+                    example_start.push(c.to_string());
+                    example_end.push(c.to_string());
+                }
             }
         }
+        if positions > MAX_EXAMPLE_LEN {
+            example_start.push("...".to_string());
+            example_end.push("...".to_string());
+        }
         if question {
             bail!("Mask '{}' ends in a ? use ?? to escape", arg);
         }
+        if let Some(literal) = literals.iter().find(|l| charsets.hex && !is_hex(l)) {
+            bail!("Mask '{}' contains '{}' which is not hex", arg, literal);
+        }
         Ok(Mask {
             arg,
             total: combinations,
@@ -245,6 +635,9 @@ impl Passphrase {
 #[derive(Clone, Debug)]
 pub struct UserCharsets {
     charsets: BTreeMap<usize, Wildcard>,
+    hex: bool,
+    // Warnings for charsets that repeat characters
+    duplicates: Vec<String>,
 }
 
 impl UserCharsets {
@@ -253,15 +646,80 @@ impl UserCharsets {
     }
 
     pub fn new(args: Vec<Option<String>>) -> Result<Self> {
+        Self::parse(args, false)
+    }
+
+    /// Charsets containing hex-encoded bytes
+    pub fn new_hex(args: Vec<Option<String>>) -> Result<Self> {
+        Self::parse(args, true)
+    }
+
+    fn parse(args: Vec<Option<String>>, hex: bool) -> Result<Self> {
         let mut charsets = BTreeMap::new();
+        let mut duplicates = vec![];
         for i in 0..args.len() {
             if let Some(str) = &args[i] {
                 let num = i + 1;
-                charsets.insert(num, Wildcard::new_custom(num, str)?);
+                let wildcard = Wildcard::new_custom(num, str, hex)?;
+                let expanded = Wildcard::expand_bytes(num, str, hex)?.len() as u64;
+                if expanded > wildcard.length {
+                    duplicates.push(format!(
+                        "Warning: Custom charset {} '{}' repeats {} characters which are only guessed once",
+                        num,
+                        str,
+                        expanded - wildcard.length
+                    ));
+                }
+                charsets.insert(num, wildcard);
             }
         }
 
-        Ok(Self { charsets })
+        Ok(Self {
+            charsets,
+            hex,
+            duplicates,
+        })
+    }
+
+    /// Whether the wildcard guesses bytes 0x00-0x1F other than tab
+    fn has_control_bytes(&self, flag: char) -> bool {
+        if flag == 'b' {
+            return true;
+        }
+        let Some(num) = flag.to_digit(10) else {
+            return false;
+        };
+        let num = num as usize;
+        let Some(display) = self.charsets.get(&num).and_then(|w| w.charset.as_ref()) else {
+            return false;
+        };
+        let is_control = |byte: &u8| *byte < b' ' && *byte != b'\t';
+        Wildcard::expand_bytes(num, display, self.hex)
+            .is_ok_and(|bytes| bytes.iter().any(is_control))
+    }
+
+    /// Binary charsets need 3 of the 4 custom charset slots, warning which ones the user took
+    pub fn binary_slots_warning(&self) -> Option<String> {
+        let slot = |i: &usize| format!("-{}", i);
+        let used: Vec<_> = self.charsets.keys().map(slot).collect();
+        let free: Vec<_> = (1..=4)
+            .filter(|i| !self.charsets.contains_key(i))
+            .map(|i| slot(&i))
+            .collect();
+        if free.len() >= BINARY_CHARSETS {
+            return None;
+        }
+        let free = match free.is_empty() {
+            true => "none".to_string(),
+            false => free.join(", "),
+        };
+        Some(format!(
+            "Warning: Binary charsets need {} of the custom charset slots -1 to -4 but {} are used by your charsets (free: {}) so seeds are guessed in the slower stdin mode\n Combine your charsets into a single slot such as {} to leave the other 3 slots free",
+            BINARY_CHARSETS,
+            used.join(", "),
+            free,
+            used[0]
+        ))
     }
 
     pub fn add_binary_charsets(&mut self, entropy_bits: usize) -> Result<Vec<Wildcard>> {
@@ -293,6 +751,15 @@ enum PassphraseArg {
     Mask(Mask),
 }
 
+impl PassphraseArg {
+    fn with_affixes(self, prefix: &str, suffix: &str) -> Self {
+        match self {
+            PassphraseArg::Dict(d) => PassphraseArg::Dict(d.with_affixes(prefix, suffix)),
+            PassphraseArg::Mask(m) => PassphraseArg::Mask(m.with_affixes(prefix, suffix)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Dictionary {
     combinations: Combinations<String>,
@@ -313,15 +780,38 @@ impl Attempt for Dictionary {
 }
 
 impl Dictionary {
-    pub fn new(vecs: Vec<Vec<String>>, arg: &str) -> Result<Self> {
+    pub fn new(vecs: Vec<Vec<String>>) -> Self {
         let combinations = Combinations::new(vecs);
-        if combinations.total() > MAX_DICT {
-            bail!(
-                "Dictionaries '{}' exceed 1B combinations\n  Try splitting into 2 args or reducing size",
-                arg
-            );
+        Self { combinations }
+    }
+
+    fn with_affixes(&self, prefix: &str, suffix: &str) -> Self {
+        let mut elements = vec![];
+        if !prefix.is_empty() {
+            elements.push(vec![prefix.to_string()]);
         }
-        Ok(Self { combinations })
+        elements.extend(self.combinations.elements());
+        if !suffix.is_empty() {
+            elements.push(vec![suffix.to_string()]);
+        }
+        Self::new(elements)
+    }
+
+    fn fold_accents(&self) -> Self {
+        let mut elements = vec![];
+        for element in self.combinations.elements() {
+            let mut seen: HashSet<String> = element.iter().cloned().collect();
+            let mut folded = vec![];
+            for word in element {
+                let fold = fold_accents(&word);
+                folded.push(word);
+                if seen.insert(fold.clone()) {
+                    folded.push(fold);
+                }
+            }
+            elements.push(folded);
+        }
+        Self::new(elements)
     }
 }
 
@@ -352,6 +842,17 @@ impl Mask {
         Self::new("", 1, "", "")
     }
 
+    fn with_affixes(&self, prefix: &str, suffix: &str) -> Self {
+        // literals in a hashcat mask only need '?' escaped
+        let escape = |str: &str| str.replace('?', "??");
+        Self {
+            arg: format!("{}{}{}", escape(prefix), self.arg, escape(suffix)),
+            total: self.total,
+            example_start: format!("{}{}{}", prefix, self.example_start, suffix),
+            example_end: format!("{}{}{}", prefix, self.example_end, suffix),
+        }
+    }
+
     fn prefix_wild(&mut self, wildcard: &Wildcard) {
         self.total = self.total.saturating_mul(wildcard.length);
         self.arg = format!("?{}{}", wildcard.flag, self.arg);
@@ -408,7 +909,7 @@ impl Wildcard {
         let root2 = PathBuf::new().join("charsets").join("bin");
 
         for root in [root1.clone(), root2] {
-            let path = root.join(format!("{}bit.hcchr", bin));
+            let path = resource_path(&root.join(format!("{}bit.hcchr", bin)));
             if path.exists() {
                 let path = path.to_str().expect("Is valid path");
                 return Ok(Self {
@@ -424,7 +925,7 @@ impl Wildcard {
         bail!("Could not find file '{:?}' make sure you are running in the directory with the '{}' folder", root1, HASHCAT_PATH);
     }
 
-    fn new_custom(num: usize, display: &String) -> Result<Self> {
+    fn new_custom(num: usize, display: &String, hex: bool) -> Result<Self> {
         if display.len() == 0 {
             bail!(
                 "Custom charset {} is empty, pass in characters like so: -{} 'qwerty'",
@@ -432,15 +933,161 @@ impl Wildcard {
                 num
             );
         }
+        if hex && !is_hex(display) {
+            bail!("Custom charset {} '{}' is not hex", num, display);
+        }
+        let bytes = Self::custom_bytes(num, display, hex)?;
+        let example = |byte: u8| match hex {
+            true => format!("{:02x}", byte),
+            false if byte.is_ascii() && !byte.is_ascii_control() => (byte as char).to_string(),
+            false => format!("0x{:02X}", byte),
+        };
         Ok(Self {
             flag: num.to_string().chars().next().unwrap(),
             display: format!("Custom charset '{}'", display),
-            length: display.len() as u64,
-            example_start: display.chars().next().unwrap().to_string(),
-            example_end: display.chars().last().unwrap().to_string(),
+            length: bytes.len() as u64,
+            example_start: example(bytes[0]),
+            example_end: example(bytes[bytes.len() - 1]),
             charset: Some(display.to_string()),
         })
     }
+
+    /// Unique bytes in a custom charset in order, expanding wildcards like hashcat does
+    fn custom_bytes(num: usize, display: &str, hex: bool) -> Result<Vec<u8>> {
+        // hashcat ignores duplicates in charsets
+        let mut unique = vec![];
+        for byte in Self::expand_bytes(num, display, hex)? {
+            if !unique.contains(&byte) {
+                unique.push(byte);
+            }
+        }
+        Ok(unique)
+    }
+
+    /// Every byte in a custom charset including duplicates
+    fn expand_bytes(num: usize, display: &str, hex: bool) -> Result<Vec<u8>> {
+        let mut expanded = vec![];
+        if hex {
+            for i in (0..display.len()).step_by(2) {
+                expanded.push(u8::from_str_radix(&display[i..i + 2], 16)?);
+            }
+        } else {
+            let mut chars = display.bytes();
+            while let Some(c) = chars.next() {
+                if c != b'?' {
+                    expanded.push(c);
+                    continue;
+                }
+                let lower = b'a'..=b'z';
+                let upper = b'A'..=b'Z';
+                let digits = b'0'..=b'9';
+                let special = (b' '..=b'/').chain(b':'..=b'@').chain(b'['..=b'`');
+                let special = special.chain(b'{'..=b'~');
+                match chars.next() {
+                    Some(b'l') => expanded.extend(lower),
+                    Some(b'u') => expanded.extend(upper),
+                    Some(b'd') => expanded.extend(digits),
+                    Some(b'h') => expanded.extend(digits.chain(b'a'..=b'f')),
+                    Some(b'H') => expanded.extend(digits.chain(b'A'..=b'F')),
+                    Some(b's') => expanded.extend(special),
+                    Some(b'a') => expanded.extend(lower.chain(upper).chain(digits).chain(special)),
+                    Some(b'b') => expanded.extend(0..=255),
+                    Some(b'?') => expanded.push(b'?'),
+                    _ => bail!(
+                        "Custom charset {} '{}' contains an invalid wildcard, use ?? to escape '?'",
+                        num,
+                        display
+                    ),
+                }
+            }
+        }
+        Ok(expanded)
+    }
+}
+
+/// Replaces accented latin letters with their ASCII letters and removes combining accents
+fn fold_accents(str: &str) -> String {
+    let mut folded = String::new();
+    for c in str.chars() {
+        let ascii = match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+            'ç' | 'ć' | 'č' => "c",
+            'Ç' | 'Ć' | 'Č' => "C",
+            'ď' | 'đ' => "d",
+            'Ď' | 'Đ' => "D",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+            'ğ' => "g",
+            'Ğ' => "G",
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' | 'Į' | 'İ' => "I",
+            'ł' => "l",
+            'Ł' => "L",
+            'ñ' | 'ń' | 'ň' => "n",
+            'Ñ' | 'Ń' | 'Ň' => "N",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ő' => "O",
+            'ř' => "r",
+            'Ř' => "R",
+            'ś' | 'š' | 'ş' => "s",
+            'Ś' | 'Š' | 'Ş' => "S",
+            'ť' | 'ţ' => "t",
+            'Ť' | 'Ţ' => "T",
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+            'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ů' | 'Ű' | 'Ų' => "U",
+            'ý' | 'ÿ' => "y",
+            'Ý' | 'Ÿ' => "Y",
+            'ź' | 'ż' | 'ž' => "z",
+            'Ź' | 'Ż' | 'Ž' => "Z",
+            'ß' => "ss",
+            'æ' => "ae",
+            'Æ' => "AE",
+            'œ' => "oe",
+            'Œ' => "OE",
+            // combining accents from decomposed text
+            '\u{0300}'..='\u{036F}' => "",
+            c => {
+                folded.push(c);
+                continue;
+            }
+        };
+        folded.push_str(ascii);
+    }
+    folded
+}
+
+/// Whether the string is hex-encoded bytes
+fn is_hex(str: &str) -> bool {
+    str.len().is_multiple_of(2) && str.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Decodes a plaintext hashcat printed as $HEX[...] back into its raw bytes
+pub fn decode_hex_plain(plain: &str) -> Vec<u8> {
+    match plain
+        .strip_prefix("$HEX[")
+        .and_then(|s| s.strip_suffix(']'))
+    {
+        Some(hex) if is_hex(hex) => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("is hex"))
+            .collect(),
+        _ => plain.as_bytes().to_vec(),
+    }
+}
+
+/// Prints bytes as text unless they are non-printable, in which case they are shown as $HEX[...]
+pub fn encode_hex_plain(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(str) if !str.chars().any(|c| c.is_control()) => str.to_string(),
+        _ => format!(
+            "$HEX[{}]",
+            bytes
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        ),
+    }
 }
 
 fn wildcards(charsets: &UserCharsets) -> Result<BTreeMap<char, Wildcard>> {
@@ -474,6 +1121,9 @@ fn wildcards(charsets: &UserCharsets) -> Result<BTreeMap<char, Wildcard>> {
 #[cfg(test)]
 mod tests {
     use std::fs::remove_file;
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
 
     use crate::passphrase::*;
 
@@ -572,22 +1222,72 @@ mod tests {
         assert!(pp.is_err());
     }
 
+    #[test]
+    fn reads_gzipped_dicts() {
+        let file = File::create("hc_test_dict.txt.gz").unwrap();
+        let mut encoder = GzEncoder::new(file, flate2::Compression::default());
+        encoder.write_all(b"the\r\nof\nand\n").unwrap();
+        encoder.finish().unwrap();
+
+        let dict = Passphrase::dict("./hc_test_dict.txt.gz,-", false).unwrap();
+        assert_eq!(dict.total(), 3);
+        assert_eq!(dict.begin(), "the-");
+        assert_eq!(dict.end(), "and-");
+        remove_file("hc_test_dict.txt.gz").unwrap();
+    }
+
+    #[test]
+    fn validates_dict_sizes() {
+        let args = vec!["./dicts/100k.txt".to_string(), "?d".to_string()];
+        assert!(Passphrase::validate_dict_sizes(&args, 1).is_ok());
+        // 811KB is over a limit of 0MB even though it rounds down to it
+        let err = Passphrase::validate_dict_sizes(&args, 0).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("is 1MB which exceeds the 0MB limit"));
+        let args = vec!["a,./dicts/norvig.com_ngrams_count_1w.txt".to_string()];
+        assert!(Passphrase::validate_dict_sizes(&args, 5).is_ok());
+        assert!(Passphrase::validate_dict_sizes(&args, 3).is_err());
+        let args = vec!["./dicts/missing.txt".to_string()];
+        assert!(Passphrase::validate_dict_sizes(&args, 0).is_ok());
+
+        // gzip files are limited by their decompressed size
+        let file = File::create("hc_test_large_dict.txt.gz").unwrap();
+        let mut encoder = GzEncoder::new(file, flate2::Compression::default());
+        encoder.write_all(&b"a\n".repeat(2_000_000)).unwrap();
+        encoder.finish().unwrap();
+        let args = vec!["./hc_test_large_dict.txt.gz".to_string()];
+        let small = Passphrase::validate_dict_sizes(&args, 3).is_ok();
+        let large = Passphrase::validate_dict_sizes(&args, 5).is_ok();
+        remove_file("hc_test_large_dict.txt.gz").unwrap();
+        assert!(!small);
+        assert!(large);
+    }
+
     #[test]
     fn validates_dicts() {
-        let dict = Passphrase::dict("a,./dicts/10k.txt,,./dicts/10k_upper.txt,b").unwrap();
+        let dict = Passphrase::dict("a,./dicts/10k.txt,,./dicts/10k_upper.txt,b", false).unwrap();
         assert_eq!(dict.total(), 10_000 * 10_000);
         assert_eq!(dict.begin(), "athe,THEb".to_string());
         assert_eq!(dict.end(), "apoison,POISONb".to_string());
 
-        let dict =
-            Passphrase::dict("./dicts/1k.txt,.//,./dicts/1k_cap.txt,??,./dicts/1k_upper.txt")
-                .unwrap();
+        let dict = Passphrase::dict(
+            "./dicts/1k.txt,.//,./dicts/1k_cap.txt,??,./dicts/1k_upper.txt",
+            false,
+        )
+        .unwrap();
         assert_eq!(dict.total(), 1000 * 1000 * 1000);
         assert_eq!(dict.begin(), "the./The?THE".to_string());
         assert_eq!(dict.end(), "entry./Entry?ENTRY".to_string());
 
-        assert!(Passphrase::dict("./dicts/asdf.txt").is_err());
-        assert!(Passphrase::dict("./dicts/100k.txt,./dicts/100k_cap.txt").is_err());
+        assert!(Passphrase::dict("./dicts/asdf.txt", false).is_err());
+
+        let large = "./dicts/100k.txt,./dicts/100k_cap.txt".to_string();
+        let pp = Passphrase::from_arg(&vec![large.clone()], &vec![]).unwrap();
+        assert_eq!(pp.total(), 100_000 * 100_000);
+        assert!(pp.dict_warning().unwrap().contains("10 files"));
+        assert!(pp.add_binary_charsets(3, 2).unwrap().is_none());
+        assert!(Passphrase::from_arg(&vec![large, "?d".to_string()], &vec![]).is_err());
     }
 
     fn charsets(chars: Vec<&str>) -> UserCharsets {
@@ -615,5 +1315,225 @@ mod tests {
 
         assert!(Passphrase::mask("?H ?2", &charsets(vec!["ab"])).is_err());
         assert!(Passphrase::mask("?l?", &charsets(vec![])).is_err());
+
+        let mask = Passphrase::mask(&"?d".repeat(100), &charsets(vec![])).unwrap();
+        assert_eq!(mask.begin(), "0".repeat(100));
+        let mask = Passphrase::mask(&"?da".repeat(10_000), &charsets(vec![])).unwrap();
+        assert_eq!(mask.begin(), "0a".repeat(50) + "...");
+        assert_eq!(mask.end(), "9a".repeat(50) + "...");
+        assert_eq!(mask.total(), u64::MAX);
+    }
+
+    #[test]
+    fn escapes_question_marks() {
+        let mask = Passphrase::mask("a???d", &charsets(vec![])).unwrap();
+        assert_eq!(mask, Mask::new("a???d", 10, "a?0", "a?9"));
+
+        let mask = Passphrase::mask("????", &charsets(vec![])).unwrap();
+        assert_eq!(mask, Mask::new("????", 1, "??", "??"));
+
+        let mask = Passphrase::mask("?1???1", &charsets(vec!["xy"])).unwrap();
+        assert_eq!(mask, Mask::new("?1???1", 4, "x?x", "y?y"));
+
+        let pp = Passphrase::from_arg(&vec!["a???d".to_string()], &vec![]).unwrap();
+        assert_eq!(pp.attack_mode, 3);
+        let pp = Passphrase::from_arg(&vec!["a????d".to_string()], &vec![]).unwrap();
+        assert_eq!(pp.attack_mode, 0);
+        assert_eq!(pp.begin(), "a??d");
+    }
+
+    #[test]
+    fn expands_custom_charsets() {
+        let mask = Passphrase::mask("?1", &charsets(vec!["?d?d!"])).unwrap();
+        assert_eq!(mask, Mask::new("?1", 11, "0", "!"));
+
+        let mask = Passphrase::mask("?1", &charsets(vec!["???l"])).unwrap();
+        assert_eq!(mask, Mask::new("?1", 27, "?", "z"));
+
+        let mask = Passphrase::mask("?1", &charsets(vec!["aab"])).unwrap();
+        assert_eq!(mask, Mask::new("?1", 2, "a", "b"));
+
+        assert!(UserCharsets::new(vec![Some("a?".to_string())]).is_err());
+        assert!(UserCharsets::new(vec![Some("?1".to_string())]).is_err());
+    }
+
+    #[test]
+    fn warns_about_duplicate_charsets() {
+        let charsets = vec![Some("aabc".to_string()), Some("?d?h".to_string())];
+        let pp = Passphrase::from_arg(&vec!["?1?2".to_string()], &charsets).unwrap();
+        assert_eq!(pp.total(), 3 * 16);
+        let warnings = pp.charset_warnings();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("'aabc' repeats 1 characters"));
+        assert!(warnings[1].contains("'?d?h' repeats 10 characters"));
+
+        let pp = Passphrase::from_arg(&vec!["?1".to_string()], &vec![Some("abc".to_string())]);
+        assert!(pp.unwrap().charset_warnings().is_empty());
+    }
+
+    #[test]
+    fn warns_about_control_bytes() {
+        let pp = Passphrase::from_arg(&vec!["?a?b".to_string()], &vec![]).unwrap();
+        let warning = pp.control_bytes_warning().unwrap();
+        assert!(warning.contains("Mask '?a?b' guesses control bytes such as 0x00 with '?b'"));
+
+        let charsets = vec![Some("abc".to_string()), Some("?b".to_string())];
+        let pp = Passphrase::from_arg(&vec!["?1??b".to_string()], &charsets).unwrap();
+        assert_eq!(pp.control_bytes_warning(), None);
+        let pp = Passphrase::from_arg(&vec!["?2".to_string()], &charsets).unwrap();
+        assert!(pp.control_bytes_warning().unwrap().contains("with '?2'"));
+
+        let charsets = vec![Some("0900".to_string()), Some("0920".to_string())];
+        let pp = Passphrase::from_hex_arg(&["?2".to_string()], &charsets).unwrap();
+        assert_eq!(pp.control_bytes_warning(), None);
+        let pp = Passphrase::from_hex_arg(&["?1".to_string()], &charsets).unwrap();
+        assert!(pp.control_bytes_warning().is_some());
+
+        let pp = Passphrase::from_arg(&vec!["./dicts/test.txt".to_string()], &vec![]);
+        assert_eq!(pp.unwrap().control_bytes_warning(), None);
+    }
+
+    #[test]
+    fn warns_about_binary_charset_slots() {
+        let pp = Passphrase::from_arg(&vec!["?1".to_string()], &vec![Some("ab".to_string())]);
+        assert_eq!(pp.unwrap().binary_slots_warning(), None);
+
+        let charsets = vec![None, Some("ab".to_string()), None, Some("cd".to_string())];
+        let pp = Passphrase::from_arg(&vec!["?2?4".to_string()], &charsets).unwrap();
+        let warning = pp.binary_slots_warning().unwrap();
+        assert!(warning.contains("-2, -4 are used by your charsets (free: -1, -3)"));
+        assert!(warning.contains("single slot such as -2"));
+
+        let pp = Passphrase::from_hex_arg(&["?2?4".to_string()], &charsets).unwrap();
+        assert_eq!(pp.binary_slots_warning(), None);
+    }
+
+    #[test]
+    fn orders_common_passphrases() {
+        let common = Passphrase::common().unwrap();
+        assert_eq!(common.len(), COMMON_PASSPHRASES.len());
+        assert_eq!(common[0].begin(), "the");
+        assert_eq!(common[common.len() - 1].total(), 10_000 * 10_000);
+        assert!(common.windows(2).all(|w| w[0].total() <= w[1].total()));
+    }
+
+    #[tokio::test]
+    async fn validates_hex_passphrases() {
+        let hex = vec![Some("00ff7f".to_string())];
+        let pp = Passphrase::from_hex_arg(&["00?1ab".to_string()], &hex).unwrap();
+        assert_eq!(pp.total(), 3);
+        assert_eq!(pp.begin(), "0000ab");
+        assert_eq!(pp.end(), "007fab");
+        assert!(pp.add_binary_charsets(3, 2).unwrap().is_none());
+        assert_args(
+            pp.build_args("", &Logger::off()).await,
+            "-a 3 00?1ab -1 00ff7f --hex-charset",
+        );
+
+        let pp = Passphrase::from_hex_arg(&["00,,ab".to_string()], &[]).unwrap();
+        assert_eq!(pp.begin(), "002cab");
+        assert!(Passphrase::from_hex_arg(&["0?d".to_string()], &[]).is_err());
+        assert!(Passphrase::from_hex_arg(&["./dicts/1k.txt".to_string()], &[]).is_err());
+        assert!(Passphrase::from_hex_arg(&["?1".to_string()], &[Some("0".to_string())]).is_err());
+    }
+
+    #[test]
+    fn previews_passphrases() {
+        let pp = Passphrase::from_arg(&vec!["a?d?1".to_string()], &vec![Some("xy".to_string())]);
+        let preview = pp.unwrap().preview(3).unwrap();
+        assert_eq!(preview, vec!["a0x", "a0y", "a1x"]);
+
+        let args = vec!["./dicts/test.txt".to_string(), "??".to_string()];
+        let pp = Passphrase::from_arg(&args, &vec![]).unwrap();
+        assert_eq!(pp.preview(2).unwrap(), vec!["the?", "of?"]);
+
+        let pp = Passphrase::from_hex_arg(&["00?1".to_string()], &[Some("ff".to_string())]);
+        assert_eq!(pp.unwrap().preview(5).unwrap(), vec!["$HEX[00ff]"]);
+    }
+
+    #[test]
+    fn decodes_hex_plaintexts() {
+        assert_eq!(decode_hex_plain("$HEX[00ff41]"), vec![0x00, 0xff, 0x41]);
+        assert_eq!(decode_hex_plain("$HEX[zz]"), b"$HEX[zz]".to_vec());
+        assert_eq!(decode_hex_plain("hashcat"), b"hashcat".to_vec());
+        assert_eq!(encode_hex_plain(&[0x00, 0xff, 0x41]), "$HEX[00ff41]");
+        assert_eq!(encode_hex_plain(b"a\tb"), "$HEX[610962]");
+        assert_eq!(encode_hex_plain("café".as_bytes()), "café");
+    }
+
+    #[test]
+    fn finds_newline_bytes() {
+        let pp = Passphrase::from_hex_arg(&["00?1".to_string()], &[Some("ff".to_string())]);
+        assert!(!pp.unwrap().has_newline_bytes().unwrap());
+        let pp = Passphrase::from_hex_arg(&["00?1".to_string()], &[Some("0aff".to_string())]);
+        assert!(pp.unwrap().has_newline_bytes().unwrap());
+        let pp = Passphrase::from_hex_arg(&["0d".to_string(), "?d".to_string()], &[]);
+        assert!(pp.unwrap().has_newline_bytes().unwrap());
+        let pp = Passphrase::from_arg(&vec!["a?b".to_string()], &vec![]);
+        assert!(pp.unwrap().has_newline_bytes().unwrap());
+        let pp = Passphrase::from_arg(&vec!["./dicts/test.txt".to_string()], &vec![]);
+        assert!(!pp.unwrap().has_newline_bytes().unwrap());
+    }
+
+    #[test]
+    fn folds_accents() {
+        assert_eq!(fold_accents("Café Ñandú straße"), "Cafe Nandu strasse");
+        assert_eq!(fold_accents("cafe\u{0301}"), "cafe");
+
+        std::fs::write("hc_fold_dict.txt", "café\ncafe\nnaïve\nplain").unwrap();
+        let args = vec!["./hc_fold_dict.txt,??".to_string()];
+        let pp = Passphrase::from_arg(&args, &vec![])
+            .unwrap()
+            .with_fold_accents();
+        let preview = pp.unwrap().preview(10).unwrap();
+        assert_eq!(
+            preview,
+            vec!["café?", "cafe?", "naïve?", "naive?", "plain?"]
+        );
+        remove_file("hc_fold_dict.txt").unwrap();
+
+        let hex = Passphrase::from_hex_arg(&["00".to_string()], &[]).unwrap();
+        assert!(hex.with_fold_accents().is_err());
+    }
+
+    #[test]
+    fn adds_prefix_and_suffix() {
+        let pp = Passphrase::from_arg(&vec!["./dicts/test.txt".to_string()], &vec![]).unwrap();
+        let pp = pp.with_affixes("satoshi", "2009").unwrap();
+        assert_eq!(
+            pp.preview(2).unwrap(),
+            vec!["satoshithe2009", "satoshiof2009"]
+        );
+        assert_eq!(pp.begin(), "satoshithe2009");
+
+        let pp = Passphrase::from_arg(&vec!["?d".to_string()], &vec![]).unwrap();
+        let pp = pp.with_affixes("a?", "").unwrap();
+        assert_eq!(pp.preview(2).unwrap(), vec!["a?0", "a?1"]);
+        assert_eq!(pp.total(), 10);
+
+        let args = vec!["?d".to_string(), "./dicts/test.txt".to_string()];
+        let pp = Passphrase::from_arg(&args, &vec![]).unwrap();
+        let pp = pp.with_affixes("x", "y").unwrap();
+        assert_eq!(pp.preview(2).unwrap(), vec!["x0they", "x0ofy"]);
+
+        let hex = Passphrase::from_hex_arg(&["00".to_string()], &[]).unwrap();
+        assert!(hex.with_affixes("a", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn orders_masks_by_markov() {
+        let pp = Passphrase::from_arg(&vec!["?l?d".to_string()], &vec![]).unwrap();
+        assert!(pp.has_mask());
+        let markov = pp.with_markov("./dicts/test.txt").unwrap();
+        // the stats only reorder the candidates
+        assert_eq!(markov.total(), pp.total());
+        let args = markov.build_args("", &Logger::off()).await.unwrap();
+        assert!(args[3].starts_with("--markov-hcstat2=/"));
+        assert!(args[3].ends_with("dicts/test.txt"));
+        assert_eq!(args.len(), 4);
+
+        assert!(pp.with_markov("./hc_missing.hcstat2").is_err());
+        let dict = Passphrase::from_arg(&vec!["./dicts/test.txt".to_string()], &vec![]).unwrap();
+        assert!(!dict.has_mask());
     }
 }
@@ -1,6 +1,7 @@
 use std::cmp::min;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::ops::Range;
 
 /***
    Generates PERMUTE(N, K) permutations where you want to select all K! permutations from a
@@ -23,6 +24,10 @@ pub struct Permutations<T> {
     k_permutations: u64,
     k: usize,
     index: u64,
+    // Index of the first permutation when sharded
+    start: u64,
+    // Lehmer code of the current permutation when emitting closest to the original order first
+    closest: Option<Vec<usize>>,
 }
 
 impl<T: Clone + Ord> Permutations<T> {
@@ -31,7 +36,8 @@ impl<T: Clone + Ord> Permutations<T> {
         Self::new_shard(elements, k, 0, n_permute_k(n, k))
     }
 
-    fn new_shard(elements: Vec<T>, k: usize, index: u64, len: u64) -> Self {
+    /// Generates only the permutations from `index` up to `len` in lexicographic order
+    pub fn new_shard(elements: Vec<T>, k: usize, index: u64, len: u64) -> Self {
         let k_permutations = n_permute_k(k, k);
         let combination_index = index / k_permutations;
         let permutation_index = index % k_permutations;
@@ -45,13 +51,25 @@ impl<T: Clone + Ord> Permutations<T> {
             k_permutations,
             k,
             index,
+            start: index,
+            closest: None,
         }
     }
 
+    /// Emits permutations closest to the original order first (by inversion count), cannot shard
+    pub fn closest_first(&self) -> Self {
+        let mut copy = Self::new(self.elements.clone(), self.k);
+        copy.closest = Some(vec![]);
+        copy
+    }
+
     pub fn shard(&self, num: usize) -> Vec<Permutations<T>> {
-        let shard_size = self.len / num as u64;
+        if self.closest.is_some() {
+            return vec![self.clone()];
+        }
+        let shard_size = (self.len - self.start) / num as u64;
         let mut shards = vec![];
-        let mut index = 0;
+        let mut index = self.start;
 
         while index < self.len {
             let len = min(self.len, index + shard_size);
@@ -61,11 +79,31 @@ impl<T: Clone + Ord> Permutations<T> {
         shards
     }
 
+    /// The `index` of `num` contiguous shards of near equal size
+    pub fn slice(&self, num: usize, index: usize) -> Permutations<T> {
+        let size = (self.len - self.start) as u128;
+        let bound = |i: usize| self.start + (size * i as u128 / num as u128) as u64;
+        Self::new_shard(
+            self.elements.clone(),
+            self.k,
+            bound(index),
+            bound(index + 1),
+        )
+    }
+
+    /// The indices of the permutations generated when sharded
+    pub fn range(&self) -> Range<u64> {
+        self.start..self.len
+    }
+
     pub fn len(&self) -> u64 {
         self.len
     }
 
     pub fn next(&mut self) -> Option<&Vec<T>> {
+        if self.closest.is_some() {
+            return self.next_closest();
+        }
         if self.indices.is_empty() {
             self.next_combo();
             return Some(&self.indices);
@@ -83,6 +121,25 @@ impl<T: Clone + Ord> Permutations<T> {
         return Some(&self.indices);
     }
 
+    fn next_closest(&mut self) -> Option<&Vec<T>> {
+        if self.index >= self.len {
+            return None;
+        }
+        let n = self.elements.len();
+        let code = self.closest.as_mut().expect("closest");
+        if code.is_empty() {
+            *code = vec![0; self.k];
+        } else if !next_lehmer_code(code, n) {
+            let distance: usize = code.iter().sum();
+            *code = first_lehmer_code(distance + 1, self.k, n)?;
+        }
+        self.index += 1;
+
+        let mut remaining = self.elements.clone();
+        self.indices = code.iter().map(|c| remaining.remove(*c)).collect();
+        Some(&self.indices)
+    }
+
     fn next_combo(&mut self) {
         let n = self.elements.len();
         let indices = indexed_combination(self.combination_index, n, self.k);
@@ -105,6 +162,26 @@ impl<T: Clone + Ord> Permutations<T> {
     }
 }
 
+/// Owned iterator over the permutations so standard adapters like `take` and `skip` can be used
+pub struct IntoIter<T>(Permutations<T>);
+
+impl<T: Clone + Ord> Iterator for IntoIter<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().cloned()
+    }
+}
+
+impl<T: Clone + Ord> IntoIterator for Permutations<T> {
+    type Item = Vec<T>;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
 /// Precomputed factorial counts
 const FACTORIAL: [u64; 21] = [
     1,
@@ -166,6 +243,35 @@ fn next_permutation<T: Ord>(list: &mut Vec<T>) -> bool {
     return true;
 }
 
+/// Lexicographically smallest Lehmer code of length `k` choosing from `n` with digits summing to `distance`
+fn first_lehmer_code(mut distance: usize, k: usize, n: usize) -> Option<Vec<usize>> {
+    let mut code = vec![0; k];
+    for i in (0..k).rev() {
+        let digit = min(distance, n - 1 - i);
+        code[i] = digit;
+        distance -= digit;
+    }
+    match distance {
+        0 => Some(code),
+        _ => None,
+    }
+}
+
+/// Advances to the next Lehmer code with the same digit sum, returns false if there is none
+fn next_lehmer_code(code: &mut [usize], n: usize) -> bool {
+    let mut suffix = 0;
+    for i in (0..code.len()).rev() {
+        if suffix > 0 && code[i] < n - 1 - i {
+            code[i] += 1;
+            let fill = first_lehmer_code(suffix - 1, code.len(), n).expect("fits");
+            code[i + 1..].copy_from_slice(&fill[i + 1..]);
+            return true;
+        }
+        suffix += code[i];
+    }
+    false
+}
+
 fn n_permute_k(n: usize, k: usize) -> u64 {
     let mut end = 1_u64;
     for i in n - k + 1..=n {
@@ -276,6 +382,44 @@ mod tests {
         all
     }
 
+    #[test]
+    fn test_slices() {
+        let permutations = Permutations::new(vec![1, 2, 3, 4, 5], 3);
+        let slices: Vec<_> = (0..7).map(|i| permutations.slice(7, i)).collect();
+        let all = assert_explode(vec![permutations]);
+        assert_eq!(assert_explode(slices), all);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let permutations = Permutations::new(vec![1, 2, 3], 2);
+        let all = assert_explode(vec![permutations.clone()]);
+        assert_eq!(permutations.clone().into_iter().collect::<Vec<_>>(), all);
+        let skipped: Vec<_> = permutations.into_iter().skip(1).take(2).collect();
+        assert_eq!(skipped, all[1..3]);
+    }
+
+    #[test]
+    fn test_closest_first() {
+        let mut perm = Permutations::new(vec![1, 2, 3], 3).closest_first();
+        assert_eq!(perm.next(), Some(&vec![1, 2, 3]));
+        assert_eq!(perm.next(), Some(&vec![1, 3, 2]));
+        assert_eq!(perm.next(), Some(&vec![2, 1, 3]));
+        assert_eq!(perm.next(), Some(&vec![2, 3, 1]));
+        assert_eq!(perm.next(), Some(&vec![3, 1, 2]));
+        assert_eq!(perm.next(), Some(&vec![3, 2, 1]));
+        assert_eq!(perm.next(), None);
+
+        let perm = Permutations::new(vec![1, 2, 3, 4, 5, 6], 4);
+        let mut all = assert_explode(vec![perm.clone()]);
+        let mut closest = assert_explode(vec![perm.closest_first()]);
+        assert_eq!(closest[0], vec![1, 2, 3, 4]);
+        assert_eq!(closest.len(), all.len());
+        all.sort();
+        closest.sort();
+        assert_eq!(all, closest);
+    }
+
     #[test]
     fn test_permutations_of_k() {
         let mut perm = Permutations::new(vec![1, 2, 3], 2);
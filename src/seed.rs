@@ -1,23 +1,34 @@
 use std::cmp::max;
 use std::collections::BTreeSet;
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
+use std::fs;
 use std::string::ToString;
+use std::sync::Arc;
 
 use anyhow::{bail, format_err, Result};
 use sha2::digest::FixedOutputReset;
 use sha2::{Digest, Sha256};
 
 use crate::combination::Combinations;
-use crate::logger::Attempt;
-use crate::passphrase::Passphrase;
+use crate::logger::{Attempt, Logger};
+use crate::passphrase::{decode_hex_plain, encode_hex_plain, Passphrase};
 use crate::SEPARATOR;
 
-const NUM_WORDS: usize = 2048;
 const BIP39_BYTE_OFFSET: u8 = 48;
 const EXACT_VALID_MAX: u64 = 100_000;
+/// Exact counts above this many seeds pause noticeably on slower machines
+const SLOW_COUNT_MIN: u64 = 10_000;
 const VALID_LENGTHS: [usize; 5] = [12, 15, 18, 21, 24];
+/// Quotes that wrap seeds pasted from documents and phones
+const PASTED_QUOTES: [char; 6] = ['"', '\'', '\u{201C}', '\u{201D}', '\u{2018}', '\u{2019}'];
+/// Invisible characters that separate pasted words like whitespace
+const ZERO_WIDTH: [char; 5] = ['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
 
-const ERR_MSG: &str = "\nSeed takes 1 arg with comma or space-separated values:
+/// The 2048 words seed indices refer to, shared between copies of a seed
+pub type Wordlist = Arc<Vec<String>>;
+
+pub const ERR_MSG: &str = "\nSeed takes 1 arg with comma or space-separated values:
  Unknown word:    '?' expands into all possible 2048 words
  Unknown suffix:  'zo?' expands into 'zone|zoo'
  Unknown prefix:  '?ppy' expands into 'happy|puppy|unhappy'
@@ -33,19 +44,82 @@ pub struct Seed {
     words: Combinations<u32>,
     encoder: SeedEncoder,
     args: Combinations<String>,
+    skip_checksum: bool,
+    wordlist: Wordlist,
+    list: Option<SeedList>,
+}
+
+/// Complete seeds from a list that are guessed instead of combinations of words
+#[derive(Debug, Clone)]
+struct SeedList {
+    seeds: Arc<Vec<Vec<u32>>>,
+    start: usize,
+    next: usize,
+    end: usize,
+}
+
+impl SeedList {
+    fn new(seeds: Vec<Vec<u32>>) -> Self {
+        let end = seeds.len();
+        Self {
+            seeds: Arc::new(seeds),
+            start: 0,
+            next: 0,
+            end,
+        }
+    }
+
+    fn next(&mut self) -> Option<&Vec<u32>> {
+        if self.next >= self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(&self.seeds[self.next - 1])
+    }
+
+    fn total(&self) -> u64 {
+        (self.end - self.start) as u64
+    }
+
+    /// Splits the seeds into at most `num` near equal shards
+    fn shard(&self, num: usize) -> Vec<SeedList> {
+        let total = self.end - self.start;
+        let num = num.clamp(1, max(total, 1));
+        (0..num)
+            .map(|index| {
+                let start = self.start + total * index / num;
+                let end = self.start + total * (index + 1) / num;
+                Self {
+                    seeds: self.seeds.clone(),
+                    start,
+                    next: start,
+                    end,
+                }
+            })
+            .collect()
+    }
 }
 
 impl Attempt for Seed {
     fn total(&self) -> u64 {
-        self.words.total()
+        match &self.list {
+            Some(list) => list.total(),
+            None => self.words.total(),
+        }
     }
 
     fn begin(&self) -> String {
-        Self::to_words(&self.words.begin())
+        match &self.list {
+            Some(list) => self.to_words(&list.seeds[list.start]),
+            None => self.to_words(&self.words.begin()),
+        }
     }
 
     fn end(&self) -> String {
-        Self::to_words(&self.words.end())
+        match &self.list {
+            Some(list) => self.to_words(&list.seeds[list.end - 1]),
+            None => self.to_words(&self.words.end()),
+        }
     }
 }
 
@@ -61,14 +135,18 @@ impl Seed {
     }
 
     pub fn from_args(arg: &str, combo_arg: &Option<usize>) -> Result<Seed> {
+        Self::from_wordlist(arg, combo_arg, &bip39_wordlist())
+    }
+
+    /// Parses seed words from a custom wordlist instead of the English BIP39 words
+    pub fn from_wordlist(
+        arg: &str,
+        combo_arg: &Option<usize>,
+        wordlist: &Wordlist,
+    ) -> Result<Seed> {
         let mut anchored = vec![];
         let mut words = vec![];
-        let split = if arg.contains(SEPARATOR) {
-            arg.split(SEPARATOR)
-        } else {
-            arg.split(" ")
-        };
-        for (index, word) in split.enumerate() {
+        for (index, word) in Self::split_words(arg)?.into_iter().enumerate() {
             if word.starts_with("^") {
                 anchored.push(index);
             }
@@ -77,44 +155,179 @@ impl Seed {
             if word.contains("?") || word.contains("|") {
                 let mut all = vec![];
                 for word in word.split("|") {
-                    let mut matching = vec![];
-                    let w = word.replace("?", "");
-
-                    for i in 0..NUM_WORDS {
-                        if word.starts_with("?")
-                            && word.ends_with("?")
-                            && BIP39_WORDS[i].contains(&w)
-                        {
-                            matching.push(i as u32);
-                        } else if word.starts_with("?") && BIP39_WORDS[i].ends_with(&w) {
-                            matching.push(i as u32);
-                        } else if word.ends_with("?") && BIP39_WORDS[i].starts_with(&w) {
-                            matching.push(i as u32);
-                        } else if BIP39_WORDS[i] == &w {
-                            matching.push(i as u32);
-                        }
-                    }
-
+                    let matching = matching_words(word, wordlist);
                     if matching.is_empty() {
                         bail!("No matching seed words for '{}' found{}", word, ERR_MSG);
                     }
                     all.extend(matching);
                 }
                 words.push(all);
-            } else if BIP39_WORDS.contains(&word.as_str()) {
-                let num = BIP39_WORDS.iter().position(|&w| w == word).unwrap();
+            } else if let Some(num) = wordlist.iter().position(|w| *w == word) {
                 words.push(vec![num as u32]);
             } else {
                 bail!("Unknown seed word '{}' found{}", word, ERR_MSG);
             }
         }
 
+        if combo_arg.is_none() {
+            Self::validate_feasible(&words)?;
+        }
         let words = match combo_arg {
             None => Combinations::new(words),
             Some(combo) => Self::validate_combinations(words, *combo, anchored)?,
         };
 
-        Ok(Self::from_words(words))
+        let mut seed = Self::from_words(words);
+        seed.wordlist = wordlist.clone();
+        Ok(seed)
+    }
+
+    /// Parses complete seeds, one per line, that are guessed in order instead of combining words
+    pub fn from_list(list: &str, wordlist: &Wordlist) -> Result<Seed> {
+        let mut seeds: Vec<Vec<u32>> = vec![];
+        for (line, arg) in list.lines().enumerate() {
+            let mut seed = vec![];
+            for word in Self::split_words(arg)? {
+                match wordlist.iter().position(|w| *w == word) {
+                    Some(num) => seed.push(num as u32),
+                    None => bail!(
+                        "Unknown seed word '{}' on line {} of the seed list (wildcards are not allowed)",
+                        word,
+                        line + 1
+                    ),
+                }
+            }
+            if seed.is_empty() {
+                continue;
+            }
+            if let Some(first) = seeds.first() {
+                if first.len() != seed.len() {
+                    bail!(
+                        "Seed on line {} has {} words but the first seed has {}, every seed in the list must be the same length",
+                        line + 1,
+                        seed.len(),
+                        first.len()
+                    );
+                }
+            }
+            seeds.push(seed);
+        }
+        let Some(first) = seeds.first() else {
+            bail!("The seed list contains no seeds, put one complete seed on each line");
+        };
+
+        // every position is guessed so hashcat returns the words of the matching seed
+        let all: Vec<u32> = (0..wordlist.len() as u32).collect();
+        let mut seed = Self::from_words(Combinations::new(vec![all; first.len()]));
+        seed.wordlist = wordlist.clone();
+        seed.list = Some(SeedList::new(seeds));
+        Ok(seed)
+    }
+
+    fn split_words(arg: &str) -> Result<Vec<&str>> {
+        // Unicode whitespace such as tabs and non-breaking spaces also separates words
+        let is_space = |c: char| c.is_whitespace() || ZERO_WIDTH.contains(&c);
+        let split: Vec<&str> = if arg.contains(SEPARATOR) {
+            arg.split(SEPARATOR).collect()
+        } else {
+            arg.split(is_space).collect()
+        };
+        // Skips blank words from trailing separators or repeated whitespace when pasting
+        let split: Vec<&str> = split
+            .into_iter()
+            .map(|word| word.trim_matches(|c| is_space(c) || PASTED_QUOTES.contains(&c)))
+            .map(Self::strip_numbering)
+            .filter(|word| !word.is_empty())
+            .collect();
+        for word in &split {
+            if word.contains(char::is_whitespace) {
+                bail!(
+                    "Seed word '{}' contains a space, separate words with either '{}' or spaces but not both",
+                    word,
+                    SEPARATOR
+                );
+            }
+        }
+        Ok(split)
+    }
+
+    /// Every seed length that '--combinations' could fill from the words supplied
+    pub fn pool_lengths(arg: &str) -> Result<Vec<usize>> {
+        let pool = Self::split_words(arg)?.len();
+        let lengths: Vec<_> = VALID_LENGTHS
+            .into_iter()
+            .filter(|len| *len <= pool)
+            .collect();
+        if lengths.is_empty() {
+            bail!(
+                "Only {} words supplied but trying every seed length needs at least {}",
+                pool,
+                VALID_LENGTHS[0]
+            );
+        }
+        Ok(lengths)
+    }
+
+    /// Reorders words transcribed across the rows of a grid into the order read down its columns
+    /// e.g. 'column:4,3' for 12 words written in 4 rows of 3
+    pub fn reorder_args(arg: &str, order: &str) -> Result<String> {
+        let err = || {
+            format_err!(
+                "Order '{}' should be 'column:ROWS,COLUMNS' e.g. 'column:4,3'",
+                order
+            )
+        };
+        let grid = order.strip_prefix("column:").ok_or_else(err)?;
+        let (rows, columns) = grid.split_once(',').ok_or_else(err)?;
+        let rows: usize = rows.trim().parse().map_err(|_| err())?;
+        let columns: usize = columns.trim().parse().map_err(|_| err())?;
+
+        let words = Self::split_words(arg)?;
+        if rows * columns != words.len() {
+            bail!(
+                "Order '{}' has {} positions but {} seed words were given",
+                order,
+                rows * columns,
+                words.len()
+            );
+        }
+        let mut reordered = vec![""; words.len()];
+        for row in 0..rows {
+            for column in 0..columns {
+                reordered[column * rows + row] = words[row * columns + column];
+            }
+        }
+        Ok(reordered.join(SEPARATOR))
+    }
+
+    /// Removes numbering like '1.' or '12)' pasted from backup cards e.g. '1. abandon 2. ability'
+    fn strip_numbering(word: &str) -> &str {
+        let rest = word.trim_start_matches(|c: char| c.is_ascii_digit());
+        if rest.len() == word.len() {
+            return word;
+        }
+        match rest.strip_prefix('.').or(rest.strip_prefix(')')) {
+            Some(rest) => rest.trim(),
+            None => word,
+        }
+    }
+
+    /// Fails before expanding seeds that have over 2^64 possibilities
+    fn validate_feasible(words: &[Vec<u32>]) -> Result<()> {
+        let total = words
+            .iter()
+            .try_fold(1_u64, |total, word| total.checked_mul(word.len() as u64));
+        if total.is_some() {
+            return Ok(());
+        }
+        let unknown = words
+            .iter()
+            .filter(|word| word.len() == BIP39_WORDS.len())
+            .count();
+        bail!(
+            "You have {} fully-unknown words so there are over 2^64 seeds which is infeasible to guess\nProvide more known words or narrow down unknown words with wildcards like 'zo?' or 'puppy|zoo'",
+            unknown
+        );
     }
 
     pub fn hash_ratio(&self) -> f64 {
@@ -122,6 +335,77 @@ impl Seed {
         self.total() as f64 / valid
     }
 
+    /// Tries word orders closest to the given order first when using combinations
+    pub fn with_closest_first(&self) -> Self {
+        let mut copy = self.clone();
+        copy.words = copy.words.closest_first();
+        copy
+    }
+
+    /// Also guesses the words at two positions e.g. '3,7' (counting from 1) swapped with each other
+    pub fn with_swap(&self, positions: &str) -> Result<Self> {
+        let err = || format_err!("Swap '{}' should be two positions e.g. '3,7'", positions);
+        let (first, second) = positions.split_once(SEPARATOR).ok_or_else(err)?;
+        let first: usize = first.trim().parse().map_err(|_| err())?;
+        let second: usize = second.trim().parse().map_err(|_| err())?;
+        let len = self.words.len();
+        for position in [first, second] {
+            if position == 0 || position > len {
+                bail!("Swap position {} must be between 1 and {}", position, len);
+            }
+        }
+        if first == second {
+            bail!("Swap positions must be different but both are {}", first);
+        }
+        let mut copy = self.clone();
+        copy.words = Combinations::permute(self.words.elements(), vec![first - 1, second - 1], len);
+        Ok(copy)
+    }
+
+    /// Guesses only the `index` (from 0) of `num` near equal slices so separate machines can split the seeds
+    pub fn with_shard(&self, num: usize, index: usize) -> Result<Self> {
+        if index >= num {
+            bail!("Shard index {} must be less than the {} shards", index, num);
+        }
+        if let Some(list) = &self.list {
+            if list.total() < num as u64 {
+                bail!(
+                    "Unable to split {} seeds into {} shards, try using fewer shards",
+                    list.total(),
+                    num
+                );
+            }
+            let mut copy = self.clone();
+            copy.list = Some(list.shard(num).swap_remove(index));
+            return Ok(copy);
+        }
+        let Some(words) = self.words.slice(num, index) else {
+            bail!(
+                "Unable to split {} seeds into {} shards, try guessing more seed words or using fewer shards",
+                Logger::format_num(self.total()),
+                num
+            );
+        };
+        let mut copy = self.clone();
+        copy.words = words;
+        Ok(copy)
+    }
+
+    /// Guesses seeds with invalid checksums for wallets that never checked them
+    pub fn with_skip_checksum(&self) -> Self {
+        let mut copy = self.clone();
+        copy.skip_checksum = true;
+        copy
+    }
+
+    /// Hashcat recomputes the checksum bits of a guessed last word so only a known last word can skip it
+    pub fn validate_skip_checksum(&self) -> Result<()> {
+        if self.skip_checksum && self.words.fixed_positions().last() == Some(&None) {
+            bail!("Skipping checksums requires the last seed word to be known since hashcat computes the checksum bits of a guessed last word\nEnter the last word from your backup and use '?' for the other words you don't know");
+        }
+        Ok(())
+    }
+
     pub fn with_pure_gpu(&self, is_pure_gpu: bool) -> Self {
         let mut copy = self.clone();
         copy.encoder.is_pure_gpu = is_pure_gpu;
@@ -149,6 +433,9 @@ impl Seed {
             words,
             encoder,
             args,
+            skip_checksum: false,
+            wordlist: bip39_wordlist(),
+            list: None,
         }
     }
 
@@ -169,8 +456,28 @@ impl Seed {
         max_args: u64,
         passphrase: &Option<Passphrase>,
     ) -> Result<Option<(Seed, Passphrase)>> {
-        if self.words.permutations() > 1 {
+        let Some((seed, guesses)) = self.binary_args(max_args) else {
             return Ok(None);
+        };
+        let passphrase = passphrase.clone().unwrap_or(Passphrase::empty_mask());
+        if let Some(passphrase) =
+            passphrase.add_binary_charsets(guesses, self.encoder.entropy_bits)?
+        {
+            return Ok(Some((seed, passphrase)));
+        }
+        return Ok(None);
+    }
+
+    /// Whether the seed could be guessed with binary charsets if the passphrase allows them
+    pub fn supports_binary_charsets(&self, max_args: u64) -> bool {
+        self.binary_args(max_args).is_some()
+    }
+
+    /// The seed with hash args for binary charsets and the number of guessed words
+    fn binary_args(&self, max_args: u64) -> Option<(Seed, usize)> {
+        // binary charsets compute the checksum on the GPU
+        if self.words.permutations() > 1 || self.skip_checksum || self.list.is_some() {
+            return None;
         }
 
         let mut seed = self.clone();
@@ -193,20 +500,10 @@ impl Seed {
             }
         }
         seed.args = Combinations::new(args);
-        if !last_question {
-            return Ok(None);
-        }
-        if seed.args.total() > max_args {
-            return Ok(None);
+        if !last_question || seed.args.total() > max_args {
+            return None;
         }
-
-        let passphrase = passphrase.clone().unwrap_or(Passphrase::empty_mask());
-        if let Some(passphrase) =
-            passphrase.add_binary_charsets(guesses, self.encoder.entropy_bits)?
-        {
-            return Ok(Some((seed, passphrase)));
-        }
-        return Ok(None);
+        Some((seed, guesses))
     }
 
     fn validate_combinations(
@@ -215,30 +512,49 @@ impl Seed {
         anchored: Vec<usize>,
     ) -> Result<Combinations<u32>> {
         let combo_str = format!("Seed word length from '--combinations' is {}", combo);
-        let num = combo - anchored.len();
+        let summary = Self::combinations_summary(words.len(), combo, anchored.len());
         if !VALID_LENGTHS.contains(&combo) {
-            bail!("{} must be one of {:?}", combo_str, VALID_LENGTHS);
+            bail!(
+                "{} must be one of {:?}{}",
+                combo_str,
+                VALID_LENGTHS,
+                summary
+            );
         }
         if words.len() < combo {
             bail!(
-                "{} but only {} possible words supplied",
+                "{} but only {} possible words supplied{}",
                 combo_str,
-                words.len()
+                words.len(),
+                summary
+            );
+        }
+        if anchored.len() > combo {
+            bail!(
+                "{} but {} words are anchored{}",
+                combo_str,
+                anchored.len(),
+                summary
             );
         }
+        let num = combo - anchored.len();
         if num >= 21 {
             bail!(
-                "Attempting {}! permutations is infeasible, try anchoring more words with '^' prefix",
-                num
+                "Attempting {}! permutations is infeasible, try anchoring more words with '^' prefix{}",
+                num,
+                summary
             );
         }
+        Self::validate_permutation_total(&words, num, &anchored)
+            .map_err(|err| format_err!("{}{}", err, summary))?;
         let mut indices = vec![];
         for i in 0..words.len() {
             if anchored.contains(&i) && i >= combo {
                 bail!(
-                    "{} but attempting to anchor a word at location {}",
+                    "{} but attempting to anchor a word at location {}{}",
                     combo_str,
-                    i + 1
+                    i + 1,
+                    summary
                 );
             }
             if !anchored.contains(&i) {
@@ -248,24 +564,105 @@ impl Seed {
         Ok(Combinations::permute(words, indices, combo))
     }
 
+    /// Explains how the supplied words are permuted to help fix '--combinations' errors
+    fn combinations_summary(words: usize, combo: usize, anchored: usize) -> String {
+        let permuted = words.saturating_sub(anchored);
+        let positions = combo.saturating_sub(anchored);
+        let mut total = Some(1_u64);
+        for i in 0..positions {
+            let choices = permuted.saturating_sub(i) as u64;
+            total = total.and_then(|t| t.checked_mul(choices));
+        }
+        let total = match total {
+            None => "Over 2^64".to_string(),
+            Some(total) => total.to_string(),
+        };
+        format!(
+            "\n\nWith --combinations {} and {} words supplied:\n {} anchored words with '^' stay in place\n {} words permute into the other {} positions ({} left out of each guess)\n {} permutations (before expanding any '?' or '|')",
+            combo,
+            words,
+            anchored,
+            permuted,
+            positions,
+            permuted.saturating_sub(positions),
+            total
+        )
+    }
+
+    /// Upper bound on permutations multiplied by the wildcard options to fail before estimating
+    fn validate_permutation_total(
+        words: &[Vec<u32>],
+        num: usize,
+        anchored: &[usize],
+    ) -> Result<()> {
+        let mut options = vec![];
+        let mut total = Some(1_u64);
+        for (i, word) in words.iter().enumerate() {
+            if anchored.contains(&i) {
+                total = total.and_then(|t| t.checked_mul(word.len() as u64));
+            } else {
+                options.push(word.len() as u64);
+            }
+        }
+        let unanchored = options.len() as u64;
+        options.sort_unstable_by(|a, b| b.cmp(a));
+        for (i, option) in options.iter().take(num).enumerate() {
+            let permutations = unanchored - i as u64;
+            total = total.and_then(|t| t.checked_mul(permutations * option));
+        }
+
+        if total.is_none() {
+            let wildcards = words.iter().filter(|w| w.len() > 1).count();
+            bail!(
+                "Permuting {} words with {} wildcard words could exceed 2^64 guesses, try anchoring more words with '^' prefix or using fewer '?' wildcards",
+                num,
+                wildcards
+            );
+        }
+        Ok(())
+    }
+
+    /// Warns when --combinations is likely a misunderstanding of how permuting works
+    pub fn combinations_warning(&self) -> Option<String> {
+        let len = self.words.len();
+        let elements = self.words.elements();
+        if self.words.permuted_len() != len || elements.len() != len {
+            return None;
+        }
+        if elements.iter().all(|word| word.len() == 1) {
+            return Some(format!(
+                "Warning: All {} words are known so '--combinations' only reorders them, remove it if the order is correct",
+                len
+            ));
+        }
+        Some(format!(
+            "Warning: Permuting all {} words since none are anchored, prefix words in the correct position with '^'",
+            len
+        ))
+    }
+
     /// Returns the complete found seed
     pub fn found(&self, found: Option<String>) -> Result<Finished> {
         if let Some(found) = found {
             let mut seed = vec![];
-            let mut split = found.split(",");
+            // hashcat prints non-printable plaintexts as $HEX[...]
+            let bytes = decode_hex_plain(&found);
+            let mut split = bytes.split(|b| *b == b',');
             for element in &self.words.fixed_positions() {
                 match *element {
-                    Some(index) => seed.push(BIP39_WORDS[index as usize]),
+                    Some(index) => seed.push(BIP39_WORDS[index as usize].to_string()),
                     None => {
                         let next = split.next();
-                        seed.push(next.ok_or(format_err!("Not enough words in {}", found))?);
+                        let next = next.ok_or(format_err!("Not enough words in {}", found))?;
+                        seed.push(String::from_utf8_lossy(next).to_string());
                     }
                 }
             }
-            let passphrase = split.next().unwrap_or("");
+            // passphrases may contain the separator
+            let passphrase: Vec<_> = split.collect();
             Ok(Finished::new(
                 &seed.join(SEPARATOR),
-                passphrase,
+                &encode_hex_plain(&passphrase.join(&b',')),
                 self.encoder.is_pure_gpu,
             ))
         } else {
@@ -275,6 +672,14 @@ impl Seed {
 
     pub fn shard_words(&self, min: usize) -> Vec<Seed> {
         let mut shards = vec![];
+        if let Some(list) = &self.list {
+            for shard_list in list.shard(min) {
+                let mut s = self.clone();
+                s.list = Some(shard_list);
+                shards.push(s);
+            }
+            return shards;
+        }
         for shard_words in self.words.shard(min) {
             let mut s = self.clone();
             s.words = shard_words;
@@ -284,13 +689,26 @@ impl Seed {
     }
 
     pub fn valid_seeds(&self) -> u64 {
-        if self.total() < EXACT_VALID_MAX {
+        if self.skip_checksum {
+            return self.total();
+        }
+        if self.is_exact_count() {
             return self.exact_valid_seeds();
         }
         let divisor = 2_u64.pow(self.words.len() as u32 / 3);
         self.total() / divisor
     }
 
+    /// Whether `valid_seeds` counts every checksum rather than estimating
+    pub fn is_exact_count(&self) -> bool {
+        self.total() < EXACT_VALID_MAX
+    }
+
+    /// Whether counting the valid seeds takes long enough that users should see a status
+    pub fn is_slow_count(&self) -> bool {
+        !self.skip_checksum && self.is_exact_count() && self.total() >= SLOW_COUNT_MIN
+    }
+
     fn exact_valid_seeds(&self) -> u64 {
         let mut seed = self.clone();
         let mut num = 0;
@@ -301,7 +719,10 @@ impl Seed {
     }
 
     pub fn next_valid(&mut self) -> Option<Vec<u8>> {
-        while let Some(next) = self.words.next() {
+        if self.skip_checksum {
+            return self.next_encoded();
+        }
+        while let Some(next) = Self::next_words(&mut self.words, &mut self.list) {
             if self.encoder.valid_checksum(next) {
                 return Some(self.encoder.encode_words(next));
             }
@@ -309,49 +730,218 @@ impl Seed {
         None
     }
 
+    /// The first seeds with valid checksums in the order they are guessed
+    pub fn preview(&self, num: usize) -> Vec<String> {
+        self.valid_words().take(num).collect()
+    }
+
+    /// Every seed with a valid checksum in the order they are guessed
+    pub fn valid_words(&self) -> impl Iterator<Item = String> {
+        let mut seed = self.clone();
+        std::iter::from_fn(move || {
+            while let Some(next) = Self::next_words(&mut seed.words, &mut seed.list) {
+                if seed.skip_checksum || seed.encoder.valid_checksum(next) {
+                    return Some(Self::join_words(&seed.wordlist, next));
+                }
+            }
+            None
+        })
+    }
+
     pub fn next_encoded(&mut self) -> Option<Vec<u8>> {
-        if let Some(next) = self.words.next() {
+        if let Some(next) = Self::next_words(&mut self.words, &mut self.list) {
             return Some(self.encoder.encode_words(next));
         }
         None
     }
 
     pub fn next(&mut self) -> Option<&Vec<u32>> {
-        self.words.next()
+        Self::next_words(&mut self.words, &mut self.list)
+    }
+
+    /// Takes the seeds from the list if there is one, borrowing only the fields it needs
+    fn next_words<'a>(
+        words: &'a mut Combinations<u32>,
+        list: &'a mut Option<SeedList>,
+    ) -> Option<&'a Vec<u32>> {
+        match list {
+            Some(list) => list.next(),
+            None => words.next(),
+        }
     }
 
     pub fn validate_length(&self) -> Result<()> {
-        if VALID_LENGTHS.contains(&self.words.len()) {
+        let len = self.words.len();
+        if VALID_LENGTHS.contains(&len) {
             return Ok(());
         }
+        let hint = if VALID_LENGTHS.contains(&(len + 1)) {
+            "\nOne word may be missing, use '?' for any word you don't know"
+        } else if VALID_LENGTHS.contains(&len.saturating_sub(1)) {
+            "\nThere may be an extra word, check for a repeated word or extra text copied with the seed"
+        } else {
+            ""
+        };
         bail!(
-            "Invalid number of seed words '{}' should be one of {:?}",
-            self.words.len(),
-            VALID_LENGTHS
+            "Invalid number of seed words '{}' should be one of {:?}{}",
+            len,
+            VALID_LENGTHS,
+            hint
         );
     }
 
-    pub fn to_words(indices: &Vec<u32>) -> String {
+    /// Returns every last word that completes the seed with a valid checksum
+    pub fn checksum_words(arg: &str, wordlist: &Wordlist) -> Result<Vec<String>> {
+        let arg = arg.trim();
+        let arg = match arg.contains(SEPARATOR) {
+            true => format!("{}{}?", arg, SEPARATOR),
+            false => format!("{} ?", arg),
+        };
+        let mut seed = Self::from_wordlist(&arg, &None, wordlist)?;
+        if !VALID_LENGTHS.contains(&seed.words.len()) {
+            let lengths: Vec<_> = VALID_LENGTHS.iter().map(|len| len - 1).collect();
+            bail!(
+                "Enter every word except the last, found {} words but should be one of {:?}",
+                seed.words.len() - 1,
+                lengths
+            );
+        }
+        if seed.total() != BIP39_WORDS.len() as u64 {
+            bail!("Every word except the last must be known (remove any '?' or '|')");
+        }
+
+        let mut words = vec![];
+        while let Some(next) = seed.words.next() {
+            if seed.encoder.valid_checksum(next) {
+                let last = *next.last().expect("non-empty");
+                words.push(wordlist[last as usize].clone());
+            }
+        }
+        Ok(words)
+    }
+
+    /// Fails with tailored guidance if no seeds can have a valid checksum
+    pub fn validate_checksums(&self) -> Result<()> {
+        if self.skip_checksum {
+            return Ok(());
+        }
+        if self.total() == 1 {
+            if self.clone().next_valid().is_none() {
+                bail!("The seed you entered has an invalid BIP39 checksum, one or more words is likely wrong\nTry replacing the words you are least sure about with '?'");
+            }
+            return Ok(());
+        }
+        if self.valid_seeds() == 0 {
+            bail!("None of the {} seeds you entered have a valid BIP39 checksum\nTry replacing more words with '?' or adding alternatives with '|'", self.total());
+        }
+        Ok(())
+    }
+
+    /// Ensures every word is known so only the passphrase needs guessing
+    pub fn validate_known(&self) -> Result<()> {
+        if self.total() != 1 {
+            bail!("Every seed word must be known to only guess the passphrase (remove any '?', '|' or --combinations)");
+        }
+        if self.valid_seeds() == 0 {
+            bail!("The known seed has an invalid checksum");
+        }
+        Ok(())
+    }
+
+    pub fn to_words(&self, indices: &[u32]) -> String {
+        Self::join_words(&self.wordlist, indices)
+    }
+
+    fn join_words(wordlist: &[String], indices: &[u32]) -> String {
         let mut words = vec![];
         for index in indices {
-            words.push(BIP39_WORDS[*index as usize]);
+            words.push(wordlist[*index as usize].as_str());
         }
         words.join(",")
     }
 }
 
+/// The English BIP39 words
+pub fn bip39_wordlist() -> Wordlist {
+    Arc::new(BIP39_WORDS.iter().map(|word| word.to_string()).collect())
+}
+
+/// Wordlist from a file of exactly 2048 words, one per line in index order
+pub fn load_wordlist(path: &str) -> Result<Wordlist> {
+    let file = fs::read_to_string(path)
+        .map_err(|_| format_err!("Unable to read wordlist file '{}'", path))?;
+    let words: Vec<String> = file
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if words.len() != BIP39_WORDS.len() {
+        bail!(
+            "Wordlist '{}' has {} words but must have exactly {} (one per line)",
+            path,
+            words.len(),
+            BIP39_WORDS.len()
+        );
+    }
+    let mut seen = HashSet::new();
+    for word in &words {
+        if word.contains(|c: char| c.is_whitespace() || "?|^,".contains(c)) {
+            bail!(
+                "Wordlist '{}' word '{}' contains a space or one of '?|^,'",
+                path,
+                word
+            );
+        }
+        if !seen.insert(word) {
+            bail!("Wordlist '{}' contains '{}' more than once", path, word);
+        }
+    }
+    Ok(Arc::new(words))
+}
+
+/// Returns the indices of words in the wordlist that match a word with '?' wildcards
+pub fn matching_words<S: AsRef<str>>(word: &str, wordlist: &[S]) -> Vec<u32> {
+    let mut matching = vec![];
+    let w = word.replace("?", "");
+
+    for (i, candidate) in wordlist.iter().enumerate() {
+        let candidate = candidate.as_ref();
+        if word.starts_with("?") && word.ends_with("?") && candidate.contains(&w) {
+            matching.push(i as u32);
+        } else if word.starts_with("?") && candidate.ends_with(&w) {
+            matching.push(i as u32);
+        } else if word.ends_with("?") && candidate.starts_with(&w) {
+            matching.push(i as u32);
+        } else if candidate == w {
+            matching.push(i as u32);
+        }
+    }
+    matching
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Finished {
     pub seed: Option<String>,
     pub passphrase: Option<String>,
+    pub derivation: Option<String>,
+    pub address: Option<String>,
     pub pure_gpu: bool,
+    // Percent of the guesses searched if hashcat stopped before exhausting them
+    pub stopped: Option<u64>,
+    // Further matches when finding all instead of stopping at the first
+    pub others: Vec<Finished>,
+    // Hashcat reported a match that does not derive the address
+    pub unverified: bool,
 }
 
 impl Display for Finished {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match (&self.seed, &self.passphrase) {
             (Some(seed), Some(passphrase)) => write!(f, "{} {}", seed, passphrase)?,
-            _ => write!(f, "Exhausted")?,
+            _ => match self.stopped {
+                Some(percent) => write!(f, "Stopped at {}%", percent)?,
+                None => write!(f, "Exhausted")?,
+            },
         }
         if self.pure_gpu {
             write!(f, " (Pure)")?
@@ -367,17 +957,62 @@ impl Finished {
         Finished {
             seed: Some(seed.to_string()),
             passphrase: Some(passphrase.to_string()),
+            derivation: None,
+            address: None,
             pure_gpu,
+            stopped: None,
+            others: vec![],
+            unverified: false,
         }
     }
 
+    /// Records the derivation path that produced the address
+    pub fn with_derivation(&self, derivation: Option<String>) -> Self {
+        let mut copy = self.clone();
+        copy.derivation = derivation;
+        copy
+    }
+
+    /// Records which address matched when several address types are guessed
+    pub fn with_address(&self, address: Option<String>) -> Self {
+        let mut copy = self.clone();
+        copy.address = address;
+        copy
+    }
+
     pub fn exhausted(pure_gpu: bool) -> Finished {
         Finished {
             seed: None,
             passphrase: None,
+            derivation: None,
+            address: None,
             pure_gpu,
+            stopped: None,
+            others: vec![],
+            unverified: false,
         }
     }
+
+    /// Records the matches found after this one
+    pub fn with_others(&self, others: Vec<Finished>) -> Self {
+        let mut copy = self.clone();
+        copy.others = others;
+        copy
+    }
+
+    /// Records that hashcat stopped after searching only some percent of the guesses
+    pub fn with_stopped(&self, percent: u64) -> Self {
+        let mut copy = self.clone();
+        copy.stopped = Some(percent);
+        copy
+    }
+
+    /// Records that the match could not be verified against the address
+    pub fn with_unverified(&self) -> Self {
+        let mut copy = self.clone();
+        copy.unverified = true;
+        copy
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -394,9 +1029,8 @@ struct SeedEncoder {
 impl SeedEncoder {
     pub fn new(words: Combinations<u32>, is_pure_gpu: bool) -> Self {
         let mut guessed = vec![];
-        for i in 0..words.len() {
-            let fixed = words.fixed_positions();
-            if fixed[i].is_none() {
+        for (i, fixed) in words.fixed_positions().iter().enumerate() {
+            if fixed.is_none() {
                 guessed.push(i);
             }
         }
@@ -471,6 +1105,7 @@ impl SeedEncoder {
             }
         }
 
+        // A fixed last word is already in the hash, otherwise hashcat only needs its entropy bits
         let last_choice = self.guessed.last().expect("non-empty");
         if *last_choice == wordlist.len() - 1 {
             let entropy = *last >> (11 - self.entropy_bits);
@@ -520,6 +1155,143 @@ mod tests {
         assert!(s.binary_charsets(u64::MAX, &None).unwrap().is_none());
     }
 
+    #[test]
+    fn uses_custom_wordlists() {
+        let custom: Vec<_> = BIP39_WORDS.iter().map(|w| w.to_uppercase()).collect();
+        fs::write("hc_wordlist.txt", custom.join("\n")).unwrap();
+        let wordlist = load_wordlist("hc_wordlist.txt").unwrap();
+
+        let s = Seed::from_wordlist("ZOO ZO? ?", &None, &wordlist).unwrap();
+        assert_eq!(s.total(), 2 * 2048);
+        assert_eq!(s.begin(), "ZOO,ZONE,ABANDON");
+        assert!(Seed::from_wordlist("zoo", &None, &wordlist).is_err());
+
+        let english = Seed::checksum_words(&["zoo"; 11].join(","), &bip39_wordlist());
+        let words = Seed::checksum_words(&["ZOO"; 11].join(","), &wordlist).unwrap();
+        let english: Vec<_> = english.unwrap().iter().map(|w| w.to_uppercase()).collect();
+        assert_eq!(words, english);
+
+        fs::write("hc_wordlist.txt", custom[1..].join("\n")).unwrap();
+        let err = load_wordlist("hc_wordlist.txt").err().unwrap().to_string();
+        assert!(err.contains("has 2047 words"));
+        fs::write(
+            "hc_wordlist.txt",
+            [&custom[1..], &custom[1..2]].concat().join("\n"),
+        )
+        .unwrap();
+        let err = load_wordlist("hc_wordlist.txt").err().unwrap().to_string();
+        assert!(err.contains("more than once"));
+        fs::remove_file("hc_wordlist.txt").unwrap();
+    }
+
+    #[test]
+    fn finds_checksum_words() {
+        let words = Seed::checksum_words(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo",
+            &bip39_wordlist(),
+        )
+        .unwrap();
+        assert_eq!(words.len(), 128);
+        assert!(words.contains(&"wrong".to_string()));
+        assert!(!words.contains(&"zoo".to_string()));
+
+        let words = Seed::checksum_words(&vec!["zoo"; 23].join(","), &bip39_wordlist()).unwrap();
+        assert_eq!(words.len(), 8);
+        assert!(words.contains(&"vote".to_string()));
+
+        assert!(Seed::checksum_words("zoo zoo zoo", &bip39_wordlist()).is_err());
+        assert!(Seed::checksum_words(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo ?",
+            &bip39_wordlist()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn encodes_fixed_last_word() {
+        // ability = 1, able = 2, zoo = 2047
+        let mut s =
+            Seed::from_arg("ability|zoo,zoo,zoo,able|zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo").unwrap();
+        assert_eq!(s.encoder.guessed, vec![0, 3]);
+        assert_eq!(
+            s.next_arg().unwrap(),
+            "?,2047,2047,?,2047,2047,2047,2047,2047,2047,2047,2047"
+        );
+        assert_eq!(s.next_encoded().unwrap(), vec![53, 55, 53, 56]);
+
+        let mut encoded = BTreeSet::new();
+        while let Some(next) = s.next_encoded() {
+            assert_eq!(next.len(), 4);
+            encoded.insert(next);
+        }
+        assert_eq!(encoded.len(), 3);
+        assert!(encoded.contains(&vec![84, 117, 84, 117]));
+
+        // Anchoring the last word with combinations also leaves it out of the encoding
+        let arg = "^zoo,^zoo,^zoo,^zoo,^zoo,^zoo,^zoo,^zoo,^zoo,ability,able,^zoo";
+        let mut s = Seed::from_combo(arg, 12).unwrap();
+        assert_eq!(s.encoder.guessed, vec![9, 10]);
+        let mut encoded = BTreeSet::new();
+        while let Some(next) = s.next_encoded() {
+            encoded.insert(next);
+        }
+        let expected = vec![vec![53, 55, 53, 56], vec![53, 56, 53, 55]];
+        assert_eq!(encoded, BTreeSet::from_iter(expected));
+    }
+
+    #[test]
+    fn recovers_uncommon_lengths() {
+        // (valid seed, number of valid last words, entropy bits in the last word)
+        let seeds = [
+            ("legal winner thank year wave sausage worth useful legal winner thank year wave sausage wise", 64, 6),
+            ("legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal will", 32, 5),
+            ("legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal winner thank year viable", 16, 4),
+        ];
+        for (seed, valid, entropy_bits) in seeds {
+            let words: Vec<_> = seed.split(' ').collect();
+            let (last, rest) = words.split_last().unwrap();
+            let found = Seed::checksum_words(&rest.join(" "), &bip39_wordlist()).unwrap();
+            assert_eq!(found.len(), valid);
+            assert!(found.contains(&last.to_string()));
+            assert!(Seed::from_arg(&words.join(","))
+                .unwrap()
+                .validate_checksums()
+                .is_ok());
+
+            let mut s = Seed::from_arg(&format!("{},?", rest.join(","))).unwrap();
+            assert_eq!(s.encoder.entropy_bits, entropy_bits);
+            assert_eq!(s.valid_seeds(), valid as u64);
+            let index = BIP39_WORDS.iter().position(|w| w == last).unwrap() as u32;
+            let encoded = vec![48 + entropy_bits as u8 + (index >> (11 - entropy_bits)) as u8];
+            assert!(std::iter::from_fn(|| s.next_valid()).any(|e| e == encoded));
+
+            let finished = s.found(Some(format!("{},", last))).unwrap();
+            assert_eq!(finished, Finished::new(&words.join(","), "", false));
+        }
+    }
+
+    #[test]
+    fn validates_checksums() {
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,wrong").unwrap();
+        assert!(s.validate_checksums().is_ok());
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo").unwrap();
+        let err = s.validate_checksums().unwrap_err().to_string();
+        assert!(err.contains("The seed you entered has an invalid BIP39 checksum"));
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo|zone").unwrap();
+        let err = s.validate_checksums().unwrap_err().to_string();
+        assert!(err.contains("None of the 2 seeds"));
+    }
+
+    #[test]
+    fn validates_known_seeds() {
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,wrong").unwrap();
+        assert!(s.validate_known().is_ok());
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo").unwrap();
+        assert!(s.validate_known().is_err());
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?").unwrap();
+        assert!(s.validate_known().is_err());
+    }
+
     #[test]
     fn validates_combinations() {
         let s = Seed::from_combo("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo", 12);
@@ -545,6 +1317,47 @@ mod tests {
             2432902008176640000
         );
 
+        // 20! with a wildcard fails before estimating
+        let s = Seed::from_combo(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo ? ^zoo",
+            21,
+        );
+        assert!(s.unwrap_err().to_string().contains("wildcard"));
+
+        // explains how the words permute
+        let s = Seed::from_combo(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo",
+            21,
+        );
+        let err = s.unwrap_err().to_string();
+        assert!(err.contains("21 words permute into the other 21 positions (0 left out"));
+        assert!(err.contains("Over 2^64 permutations"));
+        let s = Seed::from_combo("^zoo,^zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo", 13);
+        let err = s.unwrap_err().to_string();
+        assert!(err.contains("11 words permute into the other 11 positions (0 left out"));
+        let s = Seed::from_combo("^zoo,".repeat(13).trim_end_matches(','), 12);
+        assert!(s.unwrap_err().to_string().contains("13 words are anchored"));
+
+        // a wildcard with fewer permutations works
+        let s = Seed::from_combo("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?,^zoo", 12);
+        assert!(s.is_ok());
+
+        // warns about likely misunderstandings
+        let s = Seed::from_combo("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo", 12).unwrap();
+        assert!(s.combinations_warning().unwrap().contains("only reorders"));
+        let s = Seed::from_combo("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?", 12).unwrap();
+        assert!(s
+            .combinations_warning()
+            .unwrap()
+            .contains("none are anchored"));
+        let s = Seed::from_combo("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,^zoo", 12).unwrap();
+        assert_eq!(s.combinations_warning(), None);
+        let s =
+            Seed::from_combo("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo", 12).unwrap();
+        assert_eq!(s.combinations_warning(), None);
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo").unwrap();
+        assert_eq!(s.combinations_warning(), None);
+
         // anchor outside combo len
         let s = Seed::from_combo("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,^zoo", 12);
         assert!(s.is_err());
@@ -561,11 +1374,11 @@ mod tests {
             Some("?,?,?,?,?,?,657,65,1269,1490,789,1516".to_string())
         );
         assert_eq!(
-            Seed::to_words(s.next().unwrap()),
+            next_words(&mut s),
             "hill,hand,friend,survey,zoo,fatal,fall,amused,pact,ripple,glance,rural"
         );
         assert_eq!(
-            Seed::to_words(s.next().unwrap()),
+            next_words(&mut s),
             "hill,hand,friend,survey,zoo,able,fall,amused,pact,ripple,glance,rural"
         );
     }
@@ -579,6 +1392,8 @@ mod tests {
         assert_eq!(s.total(), 2048 * 2048);
         assert_eq!(s.valid_seeds(), 2048 * 2048 / 16);
         assert_eq!(s.hash_ratio(), 16.0);
+        assert!(!s.is_exact_count());
+        assert!(!s.is_slow_count());
 
         let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo|zone,?,?").unwrap();
         assert_eq!(s.valid_seeds(), 524288);
@@ -591,6 +1406,221 @@ mod tests {
 
         let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,z?,a?,a?,able").unwrap();
         assert_eq!(s.valid_seeds(), 4687);
+        assert!(s.is_exact_count());
+        assert!(s.is_slow_count());
+        assert!(!s.with_skip_checksum().is_slow_count());
+
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?").unwrap();
+        assert!(s.is_exact_count());
+        assert!(!s.is_slow_count());
+    }
+
+    #[test]
+    fn skips_checksums() {
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo").unwrap();
+        assert!(s.validate_checksums().is_err());
+        let mut s = s.with_skip_checksum();
+        assert!(s.validate_checksums().is_ok());
+        assert!(s.validate_skip_checksum().is_ok());
+        assert_eq!(s.valid_seeds(), 1);
+        assert_eq!(s.next_valid(), Some(vec![]));
+
+        // every guessed word is encoded including those that make the checksum invalid
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?,zoo").unwrap();
+        let mut s = s.with_skip_checksum();
+        assert!(s.validate_skip_checksum().is_ok());
+        assert_eq!(s.hash_ratio(), 1.0);
+        assert!(s.binary_charsets(u64::MAX, &None).unwrap().is_none());
+        let mut encoded = vec![];
+        while let Some(next) = s.next_valid() {
+            encoded.push(next);
+        }
+        assert_eq!(encoded.len(), 2048);
+        let expected: Vec<_> = (0..2048_u32)
+            .map(|word| {
+                let mut bytes = vec![];
+                SeedEncoder::encode_word(&mut bytes, word);
+                bytes
+            })
+            .collect();
+        assert_eq!(encoded, expected);
+
+        // only the entropy bits of a guessed last word reach hashcat
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?").unwrap();
+        assert!(s.validate_skip_checksum().is_ok());
+        let err = s.with_skip_checksum().validate_skip_checksum().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("requires the last seed word to be known"));
+    }
+
+    #[test]
+    fn lists_self_consistent_seeds() {
+        let arg = "toy donkey chaos ethics ? struggle ramp dune join nothing wait length";
+        let s = Seed::from_arg(arg).unwrap();
+        let seeds = s.preview(usize::MAX);
+        assert_eq!(seeds.len() as u64, s.valid_seeds());
+        assert!(seeds.contains(&arg.replace("?", "vapor").replace(" ", ",")));
+        assert_eq!(s.preview(2).len(), 2);
+    }
+
+    #[test]
+    fn rejects_infeasible_seeds() {
+        let err = Seed::from_arg("?,?,?,?,?,?,?,?,?,?,?,?")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("You have 12 fully-unknown words"));
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,?,?,?,?,?").unwrap();
+        assert_eq!(s.total(), 2048_u64.pow(5));
+    }
+
+    #[test]
+    fn reorders_grid_columns() {
+        let words = "toy chaos vapor ramp join wait donkey ethics struggle dune nothing length";
+        let reordered = Seed::reorder_args(words, "column:2,6").unwrap();
+        assert_eq!(
+            reordered,
+            "toy,donkey,chaos,ethics,vapor,struggle,ramp,dune,join,nothing,wait,length"
+        );
+        let reordered = Seed::reorder_args("1. a 2. b 3. c 4. d", "column:2,2").unwrap();
+        assert_eq!(reordered, "a,c,b,d");
+
+        assert!(Seed::reorder_args(words, "column:3,3").is_err());
+        assert!(Seed::reorder_args(words, "row:2,6").is_err());
+        assert!(Seed::reorder_args(words, "column:2").is_err());
+    }
+
+    #[test]
+    fn splits_into_shards() {
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,a?,?").unwrap();
+        let mut all = vec![];
+        for index in 0..3 {
+            let shard = s.with_shard(3, index).unwrap();
+            all.extend(shard.preview(usize::MAX));
+        }
+        all.sort();
+        assert_eq!(all, s.preview(usize::MAX));
+
+        let s = Seed::from_combo(
+            "^toy,^donkey,^chaos,ethics,vapor,struggle,ramp,dune,join,nothing,wait,length",
+            12,
+        )
+        .unwrap();
+        let shard = s.with_shard(4, 3).unwrap();
+        assert_eq!(shard.total(), s.total() / 4);
+
+        assert!(s.with_shard(4, 4).is_err());
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,wrong").unwrap();
+        assert!(s.with_shard(2, 0).is_err());
+    }
+
+    #[test]
+    fn supports_binary_charsets_for_the_last_word() {
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,a?,?").unwrap();
+        assert!(s.supports_binary_charsets(u64::MAX));
+        assert!(!s.supports_binary_charsets(1));
+        assert!(!s.with_skip_checksum().supports_binary_charsets(u64::MAX));
+        let s = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?,zoo").unwrap();
+        assert!(!s.supports_binary_charsets(u64::MAX));
+    }
+
+    #[test]
+    fn guesses_seed_lists() {
+        let valid = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong";
+        let invalid = "zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo";
+        let list = format!("{}\n\n{}\n", valid, invalid);
+        let s = Seed::from_list(&list, &bip39_wordlist()).unwrap();
+        assert_eq!(s.total(), 2);
+        assert_eq!(s.valid_seeds(), 1);
+        assert_eq!(s.preview(2), vec![valid.replace(" ", ",")]);
+        assert!(s.binary_charsets(u64::MAX, &None).unwrap().is_none());
+        assert_eq!(s.clone().next_arg().unwrap(), ["?"; 12].join(","));
+        assert_eq!(
+            s.found(Some(format!("{},pass", invalid))).unwrap(),
+            Finished::new(invalid, "pass", false)
+        );
+
+        let shards: Vec<_> = s.shard_words(3).iter().map(|s| s.total()).collect();
+        assert_eq!(shards, vec![1, 1]);
+        assert_eq!(s.with_shard(2, 1).unwrap().begin(), invalid);
+        assert!(s.with_shard(3, 0).is_err());
+
+        let err = Seed::from_list("zoo zo?", &bip39_wordlist()).unwrap_err();
+        assert!(err.to_string().contains("'zo?' on line 1"));
+        let err = Seed::from_list("zoo zoo\nzoo", &bip39_wordlist()).unwrap_err();
+        assert!(err.to_string().contains("line 2 has 1 words"));
+        assert!(Seed::from_list("\n", &bip39_wordlist()).is_err());
+    }
+
+    #[test]
+    fn swaps_two_words() {
+        let words = "toy,donkey,ethics,chaos,vapor,struggle,ramp,dune,join,nothing,wait,length";
+        let s = Seed::from_arg(words).unwrap().with_swap("3, 4").unwrap();
+        assert_eq!(s.total(), 2);
+        assert_eq!(
+            s.preview(usize::MAX),
+            vec!["toy,donkey,chaos,ethics,vapor,struggle,ramp,dune,join,nothing,wait,length"]
+        );
+
+        let s = Seed::from_arg(
+            "toy,do?,ethics,chaos,vapor,struggle,ramp,dune,join,nothing,wait,length",
+        )
+        .unwrap();
+        let swapped = s.with_swap("4,2").unwrap();
+        assert_eq!(swapped.total(), s.total() * 2);
+        assert_eq!(swapped.combinations_warning(), None);
+
+        assert!(s.with_swap("3").is_err());
+        assert!(s.with_swap("3,x").is_err());
+        assert!(s.with_swap("0,4").is_err());
+        assert!(s.with_swap("3,13").is_err());
+        assert!(s.with_swap("3,3").is_err());
+    }
+
+    #[test]
+    fn finds_pool_lengths() {
+        let words = "toy donkey chaos ethics vapor struggle ramp dune join nothing wait length";
+        assert_eq!(Seed::pool_lengths(words).unwrap(), vec![12]);
+        let pool = format!("{} zoo zone ? ^abandon", words);
+        assert_eq!(Seed::pool_lengths(&pool).unwrap(), vec![12, 15]);
+        assert_eq!(
+            Seed::pool_lengths(&["zoo"; 24].join(",")).unwrap(),
+            VALID_LENGTHS
+        );
+        assert!(Seed::pool_lengths("toy donkey chaos").is_err());
+    }
+
+    #[test]
+    fn skips_blank_words() {
+        let s = Seed::from_arg("zoo, zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,").unwrap();
+        assert!(s.validate_length().is_ok());
+        let s = Seed::from_arg(" zoo zoo  zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo ").unwrap();
+        assert!(s.validate_length().is_ok());
+
+        let numbered =
+            "1. zoo 2. zoo 3.zoo 4) zoo 5. zoo 6. zoo 7. zoo 8. zoo 9. zoo 10. zoo 11. zoo 12. zoo";
+        let s = Seed::from_arg(numbered).unwrap();
+        assert!(s.validate_length().is_ok());
+        let s = Seed::from_arg("1. zoo, 2. zoo, 3. ?, 4. zoo, 5. zoo, 6. zoo, 7. zoo, 8. zoo, 9. zoo, 10. zoo, 11. zoo, 12. zoo").unwrap();
+        assert_eq!(s.total(), 2048);
+        assert!(Seed::from_arg("1.5 zoo").is_err());
+
+        let pasted = "\u{201C}zoo\tzoo\u{a0}zoo\u{2003}zoo\u{200B}zoo zoo zoo zoo zoo zoo zoo \u{feff}zoo\u{201D}";
+        let s = Seed::from_arg(pasted).unwrap();
+        assert!(s.validate_length().is_ok());
+        let s =
+            Seed::from_arg("'zoo, zoo,\tzoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,?\u{200B}'").unwrap();
+        assert_eq!(s.total(), 2048);
+
+        let err = Seed::from_arg("zoo zoo,zoo").err().unwrap().to_string();
+        assert!(err.contains("separate words with either ','"));
+        let err = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo").unwrap();
+        let err = err.validate_length().err().unwrap().to_string();
+        assert!(err.contains("One word may be missing"));
+        let err = Seed::from_arg("zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo,zoo").unwrap();
+        let err = err.validate_length().err().unwrap().to_string();
+        assert!(err.contains("There may be an extra word"));
     }
 
     #[test]
@@ -607,6 +1637,30 @@ mod tests {
             s.found(Some("ability,pass".to_string())).unwrap(),
             Finished::new("ability,zoo", "pass", true)
         );
+
+        let arg = "zoo,survey,thought,^hill,^friend,^fatal,^fall,^amused,^pact,^ripple,^glance,^rural,hand";
+        let s = Seed::from_combo(arg, 12).unwrap();
+        assert_eq!(
+            s.found(Some("hand,thought,survey,a:b,,c".to_string()))
+                .unwrap(),
+            Finished::new(
+                "hand,thought,survey,hill,friend,fatal,fall,amused,pact,ripple,glance,rural",
+                "a:b,,c",
+                false
+            )
+        );
+
+        let s = Seed::from_arg("?,zoo").unwrap();
+        assert_eq!(
+            s.found(Some("$HEX[6162696c6974792c00ff41]".to_string()))
+                .unwrap(),
+            Finished::new("ability,zoo", "$HEX[00ff41]", false)
+        );
+        assert_eq!(
+            s.found(Some("$HEX[6162696c6974792c2c41]".to_string()))
+                .unwrap(),
+            Finished::new("ability,zoo", ",A", false)
+        );
     }
 
     #[test]
@@ -647,19 +1701,19 @@ mod tests {
 
         let mut seed = Seed::from_arg("zo?").unwrap().with_pure_gpu(true);
         assert_eq!(seed.total(), 2);
-        assert_eq!(Seed::to_words(seed.next().unwrap()), "zone");
-        assert_eq!(Seed::to_words(seed.next().unwrap()), "zoo");
+        assert_eq!(next_words(&mut seed), "zone");
+        assert_eq!(next_words(&mut seed), "zoo");
 
         let mut seed = Seed::from_arg("?orro?").unwrap();
         assert_eq!(seed.total(), 3);
-        assert_eq!(Seed::to_words(seed.next().unwrap()), "borrow");
-        assert_eq!(Seed::to_words(seed.next().unwrap()), "horror");
-        assert_eq!(Seed::to_words(seed.next().unwrap()), "tomorrow");
+        assert_eq!(next_words(&mut seed), "borrow");
+        assert_eq!(next_words(&mut seed), "horror");
+        assert_eq!(next_words(&mut seed), "tomorrow");
 
         let mut seed = Seed::from_arg("puppy|zo?").unwrap();
         assert_eq!(seed.total(), 3);
-        assert_eq!(Seed::to_words(seed.next().unwrap()), "puppy");
-        assert_eq!(Seed::to_words(seed.next().unwrap()), "zone");
+        assert_eq!(next_words(&mut seed), "puppy");
+        assert_eq!(next_words(&mut seed), "zone");
 
         assert!(Seed::from_arg("zz?").is_err());
         assert!(Seed::from_arg("zz").is_err());
@@ -673,6 +1727,11 @@ mod tests {
         assert_eq!(seed.next(), Some(&vec![1, 4, 5, 8]));
     }
 
+    fn next_words(seed: &mut Seed) -> String {
+        let next = seed.next().unwrap().clone();
+        seed.to_words(&next)
+    }
+
     fn zeros() -> Vec<Vec<u32>> {
         let mut zero = vec![];
         for _ in 0..12 {
@@ -727,6 +1786,36 @@ mod tests {
         assert_eq!(String::from_utf8_lossy(&result), "0,0,0,0,0,0,0,0,0,0,0,0");
     }
 
+    #[test]
+    fn encodes_every_length() {
+        let word = 0b10110011101;
+        let last = 0b10111011100;
+        // (length, checksum bits, entropy bits, encoded bytes the hashcat kernel decodes)
+        let expected = vec![
+            (12, 4, 7, vec![75, 83, 148]),
+            (15, 5, 6, vec![75, 83, 100]),
+            (18, 6, 5, vec![75, 83, 76]),
+            (21, 7, 4, vec![75, 83, 63]),
+            (24, 8, 3, vec![75, 83, 56]),
+        ];
+        for (len, checksum_bits, entropy_bits, bytes) in expected {
+            let mut words = vec![vec![0]; len];
+            words[1] = vec![word, word];
+            words[len - 1] = vec![last, last];
+            let mut seed = Seed::from_vecs(words);
+            assert_eq!(seed.encoder.checksum_bits, checksum_bits);
+            assert_eq!(seed.encoder.entropy_bits, entropy_bits);
+            assert_eq!(seed.next_encoded().unwrap(), bytes);
+
+            let mut seed = seed.with_pure_gpu(true);
+            let encoded = String::from_utf8(seed.next_encoded().unwrap()).unwrap();
+            let mut expected = vec!["0".to_string(); len];
+            expected[1] = format!("={}", word);
+            expected[len - 1] = format!("={}", last);
+            assert_eq!(encoded, expected.join(","));
+        }
+    }
+
     fn single_seed(vec: &Vec<u32>) -> Seed {
         let vecs = vec.into_iter().map(|i| vec![*i]).collect();
         Seed::from_vecs(vecs)
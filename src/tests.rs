@@ -53,8 +53,16 @@ impl Test {
 
         let run = hashcat.run(&log, false);
         let (_, result) = run.await.unwrap();
+        let result = result.with_address(None);
         if result != self.expected {
-            bail!("{} Failed: {}\nExpected: {}", name, result, self.expected);
+            bail!(
+                "{} Failed: {} {:?}\nExpected: {} {:?}",
+                name,
+                result,
+                result.derivation,
+                self.expected,
+                self.expected.derivation
+            );
         } else {
             Ok(())
         }
@@ -70,24 +78,32 @@ impl Tests {
         Self { tests: vec![] }
     }
 
-    fn test_both(&mut self, args: &str, expected: &str) {
-        self.test(args, expected, false, false);
-        self.test(args, expected, true, false);
+    fn test_both(&mut self, args: &str, expected: &str, derivation: Option<&str>) {
+        self.test(args, expected, derivation, false, false);
+        self.test(args, expected, derivation, true, false);
     }
 
-    fn test_stdin(&mut self, args: &str, expected: &str) {
-        self.test(args, expected, false, false);
+    fn test_stdin(&mut self, args: &str, expected: &str, derivation: Option<&str>) {
+        self.test(args, expected, derivation, false, false);
     }
 
-    fn test_binary(&mut self, args: &str, expected: &str) {
-        self.test(args, expected, true, true);
+    fn test_binary(&mut self, args: &str, expected: &str, derivation: Option<&str>) {
+        self.test(args, expected, derivation, true, true);
     }
 
-    fn test(&mut self, args: &str, expected: &str, pure_gpu: bool, binary: bool) {
+    fn test(
+        &mut self,
+        args: &str,
+        expected: &str,
+        derivation: Option<&str>,
+        pure_gpu: bool,
+        binary: bool,
+    ) {
         let expected: Vec<_> = expected.split(" ").collect();
+        let finished = Finished::new(expected[0], expected.get(1).unwrap_or(&""), pure_gpu);
         self.tests.push(Test {
             args: args.to_string(),
-            expected: Finished::new(expected[0], expected.get(1).unwrap_or(&""), pure_gpu),
+            expected: finished.with_derivation(derivation.map(|d| d.to_string())),
             binary,
         })
     }
@@ -97,43 +113,61 @@ pub async fn run_tests() -> Result<()> {
     let mut tests = Tests::new();
 
     tests.test_binary("-a 1Mbe4MHF4awqg2cojz8LRJErKaKyoQjsiD -s harbor,?,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,?",
-                     "harbor,acquire,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,pumpkin");
+                     "harbor,acquire,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,pumpkin", Some("m/0/0"));
 
     tests.test_binary("-a 1HJVf7UhgHKhvMyKVuQMhzrvGQ9QSGUARQ -s harbor,a?,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,? -p hashcat",
-                      "harbor,acquire,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,pumpkin hashcat");
+                      "harbor,acquire,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,pumpkin hashcat", Some("m/0/0"));
 
     tests.test_binary("-a 1HJVf7UhgHKhvMyKVuQMhzrvGQ9QSGUARQ -s harbor,acquire,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,? -p hashca?l",
-                      "harbor,acquire,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,pumpkin hashcat");
+                      "harbor,acquire,clinic,index,mix,shoe,tube,awkward,food,acquire,sustain,pumpkin hashcat", Some("m/0/0"));
 
     tests.test_stdin("-a 1CFizqjfv4kGz4PbvMviXY84Z73D7PSdR1 -s zoo,survey,thought,^hill,^friend,^fatal,^fall,^amused,^pact,^ripple,^glance,^rural,hand -c 12",
-                     "hand,thought,survey,hill,friend,fatal,fall,amused,pact,ripple,glance,rural");
+                     "hand,thought,survey,hill,friend,fatal,fall,amused,pact,ripple,glance,rural", Some("m/0/0"));
+
+    tests.test_both("-a 1GDiBt5d3ZSaGkjby2umk35FAsmDdDVjNk -s zoo,survey,thought,^hill,^friend,^fatal,^fall,^amused,^pact,^ripple,^glance,^rural,hand -c 12 -p hashca?l",
+                    "hand,thought,survey,hill,friend,fatal,fall,amused,pact,ripple,glance,rural hashcat", Some("m/0/0"));
 
     tests.test_stdin("-a 1Hh5BipqjUyFJXXynux6ReTdEN5vStpQvn -s ?,r?,weather,dish,swall?|zoo,water,mosquito,merry,icon,congress,blush,section",
-                     "there,river,weather,dish,swallow,water,mosquito,merry,icon,congress,blush,section");
+                     "there,river,weather,dish,swallow,water,mosquito,merry,icon,congress,blush,section", Some("m/0/0"));
 
     tests.test_stdin("-a 39zQn8yBDmUHswRYUgjwwEe6y5b6wDTUTi -s skill,check,filter,camera,pond,oppose,lesson,delay,rare,prepare,oak,bring,tape,fancy,pulp,voyage,coil,spot,faculty,nominee,rough,stick,?,enter",
-                     "skill,check,filter,camera,pond,oppose,lesson,delay,rare,prepare,oak,bring,tape,fancy,pulp,voyage,coil,spot,faculty,nominee,rough,stick,wide,enter");
+                     "skill,check,filter,camera,pond,oppose,lesson,delay,rare,prepare,oak,bring,tape,fancy,pulp,voyage,coil,spot,faculty,nominee,rough,stick,wide,enter", Some("m/49'/0'/0'/0/0"));
 
     tests.test_stdin("-a bc1qscpdw0smafzpwe5s9kjfstq48p6vcz0n30sccs -s p?,stumble,print,mansion,occur,client,deposit,electric,dance,olive,stay,mom -d m/0/0/?99,m/84'/0'/?2'/0/?3",
-                     "private,stumble,print,mansion,occur,client,deposit,electric,dance,olive,stay,mom");
+                     "private,stumble,print,mansion,occur,client,deposit,electric,dance,olive,stay,mom", Some("m/84'/0'/1'/0/2"));
 
     tests.test_binary("-a bc1qscpdw0smafzpwe5s9kjfstq48p6vcz0n30sccs -s private,stumble,print,mansion,occur,client,deposit,electric,dance,olive,stay,? -d m/0/0/?99|m/84'/0'/?2'/0/?3",
-                     "private,stumble,print,mansion,occur,client,deposit,electric,dance,olive,stay,mom");
+                     "private,stumble,print,mansion,occur,client,deposit,electric,dance,olive,stay,mom", Some("m/84'/0'/1'/0/2"));
 
     tests.test_both("-a xpub661MyMwAqRbcF5snxLXxdet4WwyipbK6phjJdy5ViauCkTSjQc37zm6Gyyryq1aF8Uuj4Xub9Bh7LfQo8ZmNujZVczj1FVs1wMDWrnTym39 -s very,cart,matter,object,raise,predict,water,term,easy,play,?,earn -p hashca?2 -2 zt",
-                     "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn hashcat");
+                     "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn hashcat", None);
+
+    tests.test_binary("-a xpub661MyMwAqRbcF5snxLXxdet4WwyipbK6phjJdy5ViauCkTSjQc37zm6Gyyryq1aF8Uuj4Xub9Bh7LfQo8ZmNujZVczj1FVs1wMDWrnTym39 -s very,cart,matter,object,raise,predict,water,term,easy,play,give,? -p hashca?2 -2 zt",
+                      "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn hashcat", None);
 
     tests.test_both("-a 1AeC6MA7U651BTVS5hWTGi5u9Z7tGtkE6y -s very,cart,matter,object,raise,predict,water,term,easy,play,give,earn -p ./dicts/test.txt,-,./dicts/test_cap.txt,- -p ./dicts/test.txt",
-                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn the-Of-and");
+                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn the-Of-and", Some("m/44'/0'/0'/0/0"));
 
     tests.test_both("-a 1Gmu1iEtjmnhrB8svoFDiFjYsc4sqXuU7z -s very,cart,matter,object,raise,predict,water,term,easy,play,give,earn -p ./dicts/test.txt,-- -p ?d",
-                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn and--2");
+                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn and--2", Some("m/44'/0'/0'/0/0"));
 
     tests.test_both("-a 1Hv3dB4JyhDBwo1vDzPKKJZp4SpxaoES6L -s very,cart,matter,object,raise,predict,water,term,easy,play,give,earn -p ?d?d-- -p ./dicts/test.txt",
-                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn 12--the");
+                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn 12--the", Some("m/44'/0'/0'/0/0"));
 
     tests.test_both("-a 18zpD3jMSrHAYoA1XcDLshXPJA46DocVNi -s very,cart,matter,object,raise,predict,water,term,easy,play,give,earn -p ?dmask?d",
-                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn 1mask2");
+                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn 1mask2", Some("m/44'/0'/0'/0/0"));
+
+    tests.test_binary("-a 1Jv6xYy38tzaG9eL36V5zVp7vRMaqQ9vFx -s adapt,explain,ecology,dinner,glare,old,unfair,empty,expect,road,off,suggest,dash,dolphin,?",
+                      "adapt,explain,ecology,dinner,glare,old,unfair,empty,expect,road,off,suggest,dash,dolphin,case", Some("m/0/0"));
+
+    tests.test_both("-a 16d3dH9HGimCz1aPzYGHPCM76XeEJZ6vZr -s adapt,ignore,?,fury,picture,popular,horn,soda,lady,develop,polar,fabric,desk,gossip,fat,wool,vital,account -p hashca?l",
+                    "adapt,ignore,empower,fury,picture,popular,horn,soda,lady,develop,polar,fabric,desk,gossip,fat,wool,vital,account hashcat", Some("m/0/0"));
+
+    tests.test_both("-a bc1qsdjzw4pqvplrl697zeze0m5al9ht7sepugpj2x -s adapt,liberty,enhance,hub,eagle,quality,pupil,awful,myth,word,common,misery,dignity,ivory,trend,tip,cancel,impact,spirit,print,?",
+                    "adapt,liberty,enhance,hub,eagle,quality,pupil,awful,myth,word,common,misery,dignity,ivory,trend,tip,cancel,impact,spirit,print,concert", Some("m/84'/0'/0'/0/0"));
+
+    tests.test_both("-a 1ftB12snqQT77KrugvrYVSZTKku7UipZg -s very,cart,matter,object,raise,predict,water,term,easy,play,give,earn -p 00?1?1 -1 00ff41 --passphrase-hex",
+                    "very,cart,matter,object,raise,predict,water,term,easy,play,give,earn $HEX[00ff41]", Some("m/44'/0'/0'/0/0"));
 
     let num = tests.tests.len();
     let mut passed = 0;